@@ -0,0 +1,75 @@
+use crate::config::xdg_dir;
+use healthchecks::env::{HEALTHCHECKS_API_URL, HEALTHCHECKS_TOKEN};
+use healthchecks::model::Check;
+use std::collections::hash_map::DefaultHasher;
+use std::env::var;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Profile the project was selected with, or none when it comes from the environment.
+static PROFILE: OnceLock<Option<String>> = OnceLock::new();
+
+/// Cache the checks of the project selected with the profile `name`, instead
+/// of the one selected by the environment.
+pub(crate) fn use_profile(name: Option<&str>) {
+    let _ = PROFILE.set(name.map(str::to_owned));
+}
+
+/// File the checks of the selected project are cached in. Projects are told
+/// apart by a hash of their profile, or of the API key and URL in the
+/// environment, so that the key itself is not written to disk.
+fn checks_file() -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    if let Some(Some(profile)) = PROFILE.get() {
+        profile.hash(&mut hasher);
+    } else {
+        var(HEALTHCHECKS_TOKEN).ok()?.hash(&mut hasher);
+        var(HEALTHCHECKS_API_URL).ok().hash(&mut hasher);
+    }
+    let dir = xdg_dir("XDG_CACHE_HOME", ".cache")?.join("hcctl");
+    Some(dir.join(format!("checks-{:016x}.json", hasher.finish())))
+}
+
+/// Remember the checks of the project, for completing check names without
+/// querying the API. Failures are ignored as the cache is only an optimisation.
+/// The file is only readable by the user, as the ping URLs of the checks are
+/// enough to ping them.
+pub(crate) fn store_checks(checks: &[Check]) {
+    let Some(file) = checks_file() else {
+        return;
+    };
+    if let (Some(dir), Ok(json)) = (file.parent(), serde_json::to_vec(checks)) {
+        let _ = std::fs::create_dir_all(dir).and_then(|()| write_private(&file, &json));
+    }
+}
+
+/// Write `contents` to `path`, making it only readable by the user even when
+/// it was created with wider permissions.
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(contents)
+}
+
+/// Checks of the project cached less than `max_age` ago.
+pub(crate) fn load_checks(max_age: Duration) -> Option<Vec<Check>> {
+    let file = checks_file()?;
+    let age = std::fs::metadata(&file)
+        .ok()?
+        .modified()
+        .ok()?
+        .elapsed()
+        .ok()?;
+    if age > max_age {
+        return None;
+    }
+    serde_json::from_slice(&std::fs::read(file).ok()?).ok()
+}
@@ -1,34 +1,411 @@
-use clap::Parser;
-
-#[derive(Debug)]
-pub(crate) struct Settings {
-    pub(crate) token: String,
-    pub(crate) ua: Option<String>,
-}
+use crate::completions::{check_candidates, profile_candidates};
+use crate::config::Profile;
+use crate::filter::CheckFilter;
+use crate::output::{Output, OutputFormat};
+use crate::project::Format;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::engine::ArgValueCandidates;
+use clap_complete::Shell;
+use healthchecks::model::{Channels, UniqueField};
+use jiff::tz::TimeZone;
+use jiff::{SignedDuration, Timestamp};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use uuid::Uuid;
 
 #[derive(Parser)]
 #[command(author, version, about)]
 pub(crate) struct Opts {
+    /// Profile from the configuration file to use, instead of HCCTL_PROFILE or the default profile
+    #[arg(long, global = true, add = ArgValueCandidates::new(profile_candidates))]
+    pub(crate) profile: Option<String>,
+    /// Format to print results in [default: table]
+    #[arg(short, long, global = true, value_enum)]
+    pub(crate) output: Option<OutputFormat>,
+    /// Comma separated list of fields to print, instead of the default ones
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub(crate) columns: Vec<String>,
+    /// Do not print a header row in tables, CSV and TSV
+    #[arg(long, global = true)]
+    pub(crate) no_header: bool,
     #[command(subcommand)]
     pub(crate) subcommand: SubCommand,
 }
 
+impl Opts {
+    /// How to print results, falling back to the defaults of `profile` for
+    /// the flags that were not passed.
+    pub(crate) fn output(&self, profile: &Profile) -> Output {
+        let columns = if self.columns.is_empty() {
+            profile.columns.clone().unwrap_or_default()
+        } else {
+            self.columns.clone()
+        };
+        Output {
+            format: self.output.or(profile.output).unwrap_or_default(),
+            columns,
+            no_header: self.no_header,
+        }
+    }
+}
+
 #[derive(Parser)]
 pub(crate) enum SubCommand {
+    Apply(Apply),
+    Channels(ChannelsCommand),
+    CheckStatus(CheckStatus),
+    Completions(Completions),
+    Config(Config),
+    Create(Create),
+    Delete(Delete),
+    Export(Export),
+    Import(Import),
     List(List),
+    Man(Man),
+    Pause(Pause),
+    Ping(Ping),
     Pings(Pings),
     Search(Search),
+    ServeMetrics(ServeMetrics),
+    Stats(Stats),
+    Update(Update),
+    Watch(Watch),
+}
+
+impl SubCommand {
+    /// Whether the subcommand runs against every profile instead of a single one.
+    pub(crate) fn spans_profiles(&self) -> bool {
+        match self {
+            SubCommand::List(l) => l.all_profiles,
+            SubCommand::Search(s) => s.all_profiles,
+            _ => false,
+        }
+    }
+}
+
+/// Report the state of checks as a Nagios or Icinga plugin, exiting with 0, 1, 2 or 3
+/// for OK, WARNING, CRITICAL or UNKNOWN
+#[derive(Parser)]
+pub(crate) struct CheckStatus {
+    /// UUIDs, slugs or names of the checks, every check is included if none are given
+    #[arg(add = ArgValueCandidates::new(check_candidates))]
+    pub(crate) checks: Vec<String>,
+    /// Include the checks with this tag, can be repeated
+    #[arg(long)]
+    pub(crate) tag: Vec<String>,
+}
+
+/// Print the script that registers the completions of hcctl in a shell
+#[derive(Parser)]
+pub(crate) struct Completions {
+    /// Shell to print the completions for
+    #[arg(value_enum)]
+    pub(crate) shell: Shell,
+}
+
+/// List the integrations of the project and assign them to checks
+#[derive(Parser)]
+pub(crate) struct ChannelsCommand {
+    #[command(subcommand)]
+    pub(crate) action: ChannelsAction,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum ChannelsAction {
+    /// List the integrations of the project
+    List,
+    /// Send the alerts of checks to an integration
+    Assign(AssignChannel),
+    /// Stop sending the alerts of checks to an integration
+    Unassign(AssignChannel),
+}
+
+/// Checks and the integration given to `channels assign` and `channels unassign`
+#[derive(Args)]
+pub(crate) struct AssignChannel {
+    /// Name or UUID of the checks
+    #[arg(add = ArgValueCandidates::new(check_candidates))]
+    pub(crate) check: String,
+    /// Name, kind or UUID of the integration
+    pub(crate) channel: String,
+    /// Do not ask for confirmation when several checks match
+    #[arg(short, long)]
+    pub(crate) yes: bool,
+}
+
+/// Manage the profiles in the configuration file
+#[derive(Parser)]
+pub(crate) struct Config {
+    #[command(subcommand)]
+    pub(crate) action: ConfigAction,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum ConfigAction {
+    /// Print the location of the configuration file
+    Path,
+    /// List the profiles, without their API keys
+    List,
+    /// Add a profile or change its fields
+    Set(SetProfile),
+    /// Remove a profile
+    Remove {
+        /// Name of the profile
+        #[arg(add = ArgValueCandidates::new(profile_candidates))]
+        name: String,
+    },
+    /// Use a profile when none is selected
+    Use {
+        /// Name of the profile
+        #[arg(add = ArgValueCandidates::new(profile_candidates))]
+        name: String,
+    },
+}
+
+/// Fields of a profile set by `config set`
+#[derive(Args)]
+pub(crate) struct SetProfile {
+    /// Name of the profile
+    #[arg(add = ArgValueCandidates::new(profile_candidates))]
+    pub(crate) name: String,
+    /// API key of the project, or - to read it from standard input
+    #[arg(long, conflicts_with = "api_key_command")]
+    pub(crate) api_key: Option<String>,
+    /// Shell command that prints the API key, such as a call to a secret manager
+    #[arg(long)]
+    pub(crate) api_key_command: Option<String>,
+    /// Base URL of the management API, including the version
+    #[arg(long)]
+    pub(crate) api_url: Option<String>,
+    /// User agent sent with every request
+    #[arg(long)]
+    pub(crate) user_agent: Option<String>,
+    /// Timeout for every request, in seconds
+    #[arg(long)]
+    pub(crate) timeout: Option<u64>,
+    /// Format to print results in when --output is not passed
+    #[arg(long, value_enum)]
+    pub(crate) default_output: Option<OutputFormat>,
+    /// Comma separated list of fields to print when --columns is not passed
+    #[arg(long, value_delimiter = ',')]
+    pub(crate) default_columns: Option<Vec<String>>,
+    /// Fields to remove from the profile, can be repeated
+    #[arg(long, value_enum)]
+    pub(crate) unset: Vec<ProfileField>,
+}
+
+/// Fields of a profile that `config set --unset` can remove.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ProfileField {
+    ApiKey,
+    ApiKeyCommand,
+    ApiUrl,
+    UserAgent,
+    Timeout,
+    DefaultOutput,
+    DefaultColumns,
+}
+
+/// Fields shared by the `create` and `update` subcommands
+#[derive(Args)]
+pub(crate) struct CheckFields {
+    /// Comma separated list of tags, an empty value clears them
+    #[arg(long, value_delimiter = ',')]
+    pub(crate) tags: Option<Vec<String>>,
+    /// Description of the check
+    #[arg(long)]
+    pub(crate) desc: Option<String>,
+    /// Expected time between pings, in seconds or with a m, h, d or w suffix
+    #[arg(long, value_parser = parse_duration, conflicts_with = "schedule")]
+    pub(crate) timeout: Option<Duration>,
+    /// How long to wait after a missed ping before alerting, in seconds or with a m, h, d or w suffix
+    #[arg(long, value_parser = parse_duration)]
+    pub(crate) grace: Option<Duration>,
+    /// Cron expression for when the check is expected to ping
+    #[arg(long)]
+    pub(crate) schedule: Option<String>,
+    /// Timezone of the schedule, such as Europe/Riga
+    #[arg(long, requires = "schedule")]
+    pub(crate) tz: Option<String>,
+    /// Keep the check paused when it receives pings, until it is resumed manually
+    #[arg(long)]
+    pub(crate) manual_resume: Option<bool>,
+    /// Channels to notify: * for all, an empty value for none, or a comma separated list of names or IDs
+    #[arg(long, value_parser = parse_channels)]
+    pub(crate) channels: Option<Channels>,
+}
+
+/// Create a new check
+#[derive(Parser)]
+pub(crate) struct Create {
+    /// Name of the check
+    pub(crate) name: String,
+    #[command(flatten)]
+    pub(crate) fields: CheckFields,
+    /// Return an existing check with the same values for these fields instead of creating one
+    #[arg(long, value_delimiter = ',')]
+    pub(crate) unique: Vec<UniqueField>,
+}
+
+/// Change the fields of the checks matching a name or UUID
+#[derive(Parser)]
+pub(crate) struct Update {
+    /// Name or UUID of the checks to update
+    #[arg(add = ArgValueCandidates::new(check_candidates))]
+    pub(crate) check: String,
+    /// New name for the checks
+    #[arg(long)]
+    pub(crate) name: Option<String>,
+    #[command(flatten)]
+    pub(crate) fields: CheckFields,
+    /// Do not ask for confirmation when several checks match
+    #[arg(short, long)]
+    pub(crate) yes: bool,
+}
+
+/// Delete the checks matching the given names or UUIDs
+#[derive(Parser)]
+pub(crate) struct Delete {
+    /// Names or UUIDs of the checks to delete
+    #[arg(required = true, add = ArgValueCandidates::new(check_candidates))]
+    pub(crate) checks: Vec<String>,
+    /// Do not ask for confirmation
+    #[arg(short, long)]
+    pub(crate) yes: bool,
+}
+
+/// Pause the checks matching the given names or UUIDs
+#[derive(Parser)]
+pub(crate) struct Pause {
+    /// Names or UUIDs of the checks to pause
+    #[arg(required = true, add = ArgValueCandidates::new(check_candidates))]
+    pub(crate) checks: Vec<String>,
+    /// Do not ask for confirmation when several checks match
+    #[arg(short, long)]
+    pub(crate) yes: bool,
+}
+
+/// Ping a check, reporting a success unless another kind of ping is given
+#[derive(Parser)]
+pub(crate) struct Ping {
+    /// Name or UUID of the check
+    #[arg(add = ArgValueCandidates::new(check_candidates))]
+    pub(crate) check: String,
+    /// Report a failure
+    #[arg(long, group = "kind")]
+    pub(crate) fail: bool,
+    /// Report that a run started, to measure how long it takes
+    #[arg(long, group = "kind")]
+    pub(crate) start: bool,
+    /// Log an event without changing the status of the check
+    #[arg(long, group = "kind")]
+    pub(crate) log: bool,
+    /// Report the exit status of a run, where 0 is a success and anything else a failure
+    #[arg(long, group = "kind", value_name = "CODE")]
+    pub(crate) exit_code: Option<u8>,
+    /// Body to send with the ping, such as the output of a run, or - to read it from standard input
+    #[arg(long, conflicts_with = "body_file")]
+    pub(crate) body: Option<String>,
+    /// File whose contents are sent as the body of the ping, with invalid UTF-8 replaced
+    #[arg(long)]
+    pub(crate) body_file: Option<PathBuf>,
+    /// Run ID that pairs a start ping with the ping that finishes the run
+    #[arg(long)]
+    pub(crate) rid: Option<Uuid>,
+}
+
+/// Create, update and delete checks to match a configuration file
+#[derive(Parser)]
+pub(crate) struct Apply {
+    /// TOML file with the desired checks
+    #[arg(short, long)]
+    pub(crate) file: PathBuf,
+    /// Delete checks that are not in the configuration file
+    #[arg(long)]
+    pub(crate) prune: bool,
+    /// Print the planned changes without making them
+    #[arg(long)]
+    pub(crate) dry_run: bool,
+}
+
+/// Export every check and the channels they use to a JSON or YAML document
+#[derive(Parser)]
+pub(crate) struct Export {
+    /// File to write the export to, instead of standard output
+    #[arg(short, long)]
+    pub(crate) file: Option<PathBuf>,
+    /// Format of the export, guessed from the file extension if not set
+    #[arg(long, value_enum)]
+    pub(crate) format: Option<Format>,
+}
+
+/// Recreate the checks from an export, matching channels by name and kind
+#[derive(Parser)]
+pub(crate) struct Import {
+    /// File with the export to import
+    #[arg(short, long)]
+    pub(crate) file: PathBuf,
+    /// Format of the export, guessed from the file extension if not set
+    #[arg(long, value_enum)]
+    pub(crate) format: Option<Format>,
 }
 
 /// Lists the checks in your account with their last ping
 #[derive(Parser)]
-pub(crate) struct List {}
+pub(crate) struct List {
+    /// List the checks of every profile in the configuration file
+    #[arg(long)]
+    pub(crate) all_profiles: bool,
+    #[command(flatten)]
+    pub(crate) filter: CheckFilter,
+}
 
-/// Get the last 10 pings for the given check ID
+/// Write the man pages of hcctl and its subcommands to a directory
+#[derive(Parser)]
+pub(crate) struct Man {
+    /// Directory to write the man pages to
+    pub(crate) dir: PathBuf,
+}
+
+/// Get the latest pings for the given check ID, or the checks matching a search term
 #[derive(Parser)]
 pub(crate) struct Pings {
-    /// ID of the check whose pings are being fetched
+    /// ID of the check whose pings are being fetched, or a search term for the names of checks
+    #[arg(add = ArgValueCandidates::new(check_candidates))]
     pub(crate) check_id: String,
+    /// Number of pings to show, 0 for every ping kept by the server
+    #[arg(short = 'n', long, default_value = "10")]
+    pub(crate) limit: usize,
+    /// Only show pings of these types, comma separated
+    #[arg(long = "type", value_delimiter = ',', value_parser = PING_TYPES)]
+    pub(crate) kinds: Vec<String>,
+    /// Only show pings received within this long, in seconds or with a m, h, d or w suffix, or since an RFC 3339 timestamp
+    #[arg(long, value_parser = parse_since)]
+    pub(crate) since: Option<Timestamp>,
+    /// Show times in the local timezone instead of UTC
+    #[arg(long, conflicts_with = "tz")]
+    pub(crate) local: bool,
+    /// Show times in this timezone instead of UTC, such as Europe/Riga
+    #[arg(long, value_parser = TimeZone::get)]
+    pub(crate) tz: Option<TimeZone>,
+    /// Show full timestamps, and the remote address, method, user agent, run ID and exit status of pings
+    #[arg(short, long)]
+    pub(crate) verbose: bool,
+    /// Fetch and show the body sent with each ping
+    #[arg(short, long)]
+    pub(crate) body: bool,
+    /// Keep printing new pings as they arrive, oldest first, until interrupted
+    #[arg(short, long)]
+    pub(crate) follow: bool,
+    /// Time between fetches of new pings with --follow, in seconds or with a m, h, d or w suffix
+    #[arg(short, long, value_parser = parse_interval, default_value = "10", requires = "follow")]
+    pub(crate) interval: Duration,
+    /// Exit with this code when a new failure ping arrives with --follow
+    #[arg(long, value_name = "CODE", requires = "follow")]
+    pub(crate) exit_on_fail: Option<i32>,
+    #[command(flatten)]
+    pub(crate) filter: CheckFilter,
 }
 
 /// Search for checks and show their latest pings
@@ -36,14 +413,245 @@ pub(crate) struct Pings {
 pub(crate) struct Search {
     /// Search term to find in the list of all pings
     pub(crate) search_term: String,
+    /// Search the checks of every profile in the configuration file
+    #[arg(long)]
+    pub(crate) all_profiles: bool,
+    #[command(flatten)]
+    pub(crate) filter: CheckFilter,
+}
+
+/// Serve the state of the checks as Prometheus metrics
+#[derive(Parser)]
+pub(crate) struct ServeMetrics {
+    /// Address to serve the metrics on
+    #[arg(short, long, default_value = "127.0.0.1:9887")]
+    pub(crate) listen: SocketAddr,
+    /// Time between refreshes of the checks, in seconds or with a m, h, d or w suffix
+    #[arg(short, long, value_parser = parse_interval, default_value = "60")]
+    pub(crate) interval: Duration,
+    /// Do not fetch the pings of every check for the duration of its latest run
+    #[arg(long)]
+    pub(crate) no_durations: bool,
+}
+
+/// Show the uptime, outages and run durations of the checks matching a name or UUID
+#[derive(Parser)]
+pub(crate) struct Stats {
+    /// Name or UUID of the checks
+    #[arg(add = ArgValueCandidates::new(check_candidates))]
+    pub(crate) check: String,
+    /// How far back to compute the uptime, in seconds or with a m, h, d or w suffix
+    #[arg(short, long, value_parser = parse_duration, default_value = "30d")]
+    pub(crate) window: Duration,
+}
+
+/// Show a live dashboard of the checks grouped by tag
+#[derive(Parser)]
+pub(crate) struct Watch {
+    /// Time between refreshes, in seconds or with a m, h, d or w suffix
+    #[arg(short, long, value_parser = parse_interval, default_value = "30")]
+    pub(crate) interval: Duration,
+    /// Only show checks with this tag
+    #[arg(long)]
+    pub(crate) tag: Option<String>,
+}
+
+/// Types of pings the API reports.
+const PING_TYPES: [&str; 5] = ["start", "success", "fail", "log", "ign"];
+
+/// Parse a point in time given as how long ago it was, or as an RFC 3339 timestamp.
+fn parse_since(value: &str) -> Result<Timestamp, String> {
+    if let Ok(timestamp) = value.parse() {
+        return Ok(timestamp);
+    }
+    let ago = SignedDuration::try_from(parse_duration(value)?).map_err(|e| e.to_string())?;
+    Timestamp::now().checked_sub(ago).map_err(|e| e.to_string())
+}
+
+/// Parse a duration given in seconds, or with a m, h, d or w suffix.
+pub(crate) fn parse_duration(value: &str) -> Result<Duration, String> {
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s"),
+    };
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("unknown unit '{unit}', expected s, m, h, d or w")),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{value}'"))?;
+    let seconds = number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("duration '{value}' is too large"))?;
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Parse the time between two fetches from the API, which cannot be zero.
+pub(crate) fn parse_interval(value: &str) -> Result<Duration, String> {
+    let interval = parse_duration(value)?;
+    if interval.is_zero() {
+        return Err("the interval must be at least one second".to_owned());
+    }
+    Ok(interval)
+}
+
+fn parse_channels(value: &str) -> Result<Channels, String> {
+    Ok(match value.trim() {
+        "*" => Channels::All,
+        "" => Channels::None,
+        list => Channels::List(list.split(',').map(|c| c.trim().to_owned()).collect()),
+    })
 }
 
 #[cfg(test)]
 mod test {
-    use super::Opts;
+    use super::{parse_duration, parse_interval, parse_since, Opts, SubCommand};
+    use crate::config::Profile;
+    use crate::output::OutputFormat;
+    use jiff::{SignedDuration, Timestamp};
+    use std::time::Duration;
 
     #[test]
     fn cli_assert() {
         <Opts as clap::CommandFactory>::command().debug_assert();
     }
+
+    #[test]
+    fn output_flags_are_global() {
+        let opts = <Opts as clap::Parser>::try_parse_from([
+            "hcctl",
+            "list",
+            "--output",
+            "csv",
+            "--columns",
+            "id,name",
+            "--no-header",
+        ])
+        .unwrap();
+        let output = opts.output(&Profile::default());
+        assert_eq!(output.format, crate::output::OutputFormat::Csv);
+        assert_eq!(output.columns, ["id", "name"]);
+        assert!(output.no_header);
+    }
+
+    #[test]
+    fn output_flags_override_profile_defaults() {
+        let profile = Profile {
+            output: Some(OutputFormat::Json),
+            columns: Some(vec!["name".to_owned()]),
+            ..Profile::default()
+        };
+        let parse = |args: &[&str]| <Opts as clap::Parser>::try_parse_from(args).unwrap();
+        let output = parse(&["hcctl", "list"]).output(&profile);
+        assert_eq!(output.format, OutputFormat::Json);
+        assert_eq!(output.columns, ["name"]);
+        let output = parse(&["hcctl", "list", "-o", "csv", "--columns", "id"]).output(&profile);
+        assert_eq!(output.format, OutputFormat::Csv);
+        assert_eq!(output.columns, ["id"]);
+        assert_eq!(
+            parse(&["hcctl", "list"]).output(&Profile::default()).format,
+            OutputFormat::Table
+        );
+    }
+
+    #[test]
+    fn completions_take_a_shell() {
+        let parse = |args: &[&str]| <Opts as clap::Parser>::try_parse_from(args);
+        assert!(parse(&["hcctl", "completions", "zsh"]).is_ok());
+        assert!(parse(&["hcctl", "completions", "tcsh"]).is_err());
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(86400)));
+        assert!(parse_duration("1y").is_err());
+        assert!(parse_duration("h").is_err());
+        assert_eq!(
+            parse_duration("99999999999999999w"),
+            Err("duration '99999999999999999w' is too large".to_owned())
+        );
+        assert_eq!(parse_interval("1"), Ok(Duration::from_secs(1)));
+        assert!(parse_interval("0").is_err());
+        assert!(parse_interval("0m").is_err());
+    }
+
+    #[test]
+    fn parses_ping_filters() {
+        let since = parse_since("2024-01-01T12:00:00+02:00").unwrap();
+        assert_eq!(since, "2024-01-01T10:00:00Z".parse::<Timestamp>().unwrap());
+        let hour_ago = parse_since("1h").unwrap();
+        assert!(Timestamp::now().duration_since(hour_ago) >= SignedDuration::from_hours(1));
+        assert!(parse_since("yesterday").is_err());
+
+        let opts = <Opts as clap::Parser>::try_parse_from([
+            "hcctl", "pings", "backup", "-n", "0", "--type", "fail,log",
+        ])
+        .unwrap();
+        let SubCommand::Pings(pings) = opts.subcommand else {
+            panic!("expected the pings subcommand");
+        };
+        assert_eq!(pings.limit, 0);
+        assert_eq!(pings.kinds, ["fail", "log"]);
+        assert!(<Opts as clap::Parser>::try_parse_from([
+            "hcctl", "pings", "backup", "--type", "up"
+        ])
+        .is_err());
+        assert!(<Opts as clap::Parser>::try_parse_from([
+            "hcctl", "pings", "backup", "--local", "--tz", "UTC",
+        ])
+        .is_err());
+        assert!(<Opts as clap::Parser>::try_parse_from([
+            "hcctl",
+            "pings",
+            "backup",
+            "--exit-on-fail",
+            "2",
+        ])
+        .is_err());
+        assert!(<Opts as clap::Parser>::try_parse_from([
+            "hcctl",
+            "pings",
+            "backup",
+            "-f",
+            "--exit-on-fail",
+            "2",
+            "-i",
+            "1m",
+        ])
+        .is_ok());
+    }
+
+    #[test]
+    fn pings_take_a_single_kind() {
+        let parse = |args: &[&str]| {
+            <Opts as clap::Parser>::try_parse_from(["hcctl", "ping", "backup"].iter().chain(args))
+        };
+        assert!(parse(&["--exit-code", "3", "--body", "-"]).is_ok());
+        assert!(parse(&["--fail", "--start"]).is_err());
+        assert!(parse(&["--log", "--exit-code", "0"]).is_err());
+        assert!(parse(&["--exit-code", "256"]).is_err());
+        assert!(parse(&["--body", "-", "--body-file", "log.txt"]).is_err());
+        assert!(parse(&["--rid", "not-a-uuid"]).is_err());
+    }
+
+    #[test]
+    fn timeout_conflicts_with_schedule() {
+        let result = <Opts as clap::Parser>::try_parse_from([
+            "hcctl",
+            "create",
+            "backup",
+            "--timeout",
+            "1h",
+            "--schedule",
+            "@daily",
+        ]);
+        assert!(result.is_err());
+    }
 }
@@ -1,43 +1,689 @@
 use color_eyre::{eyre::eyre, Result};
-use comfy_table::modifiers::UTF8_ROUND_CORNERS;
-use comfy_table::presets::UTF8_FULL;
-use comfy_table::{ContentArrangement, Table};
-use healthchecks::{manage, model::Check};
-use time::{format_description::well_known::Rfc3339, OffsetDateTime, UtcOffset};
+use healthchecks::model::Check;
+use serde_derive::Serialize;
+use serde_json::{Map, Value};
 use uuid::Uuid;
 
-use crate::cli::Settings;
 use healthchecks::manage::ManageClient;
-use healthchecks::model::Ping;
+use healthchecks::manage::UpsertResult;
+use healthchecks::model::{Channel, Channels, NewCheck, Ping, UpdatedCheck};
+use healthchecks::ping::PingKind;
+use healthchecks::plan::{Change, DesiredChecks, Plan};
+use healthchecks::stats::{RunDurations, Uptime};
+use jiff::tz::TimeZone;
+use jiff::{SignedDuration, Timestamp};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
 
-pub(crate) fn pings(settings: Settings, check_id: &str) -> Result<()> {
-    let client = manage::get_client(settings.token, settings.ua)?;
-    let pings = match Uuid::parse_str(check_id) {
-        Ok(_) => client.list_logged_pings(check_id)?,
-        Err(_) => search_pings(&client, check_id)?,
+use crate::cache;
+use crate::cli::{
+    self, AssignChannel, ChannelsAction, CheckFields, Create, Delete, Pause, Pings, Update,
+};
+use crate::config;
+use crate::filter::CheckFilter;
+use crate::output::{self, Output, OutputFormat, Record};
+use crate::project::{self, Format, ProjectExport};
+use crate::watch::compact_duration;
+
+/// Columns shown in tables by `pings --verbose`.
+const VERBOSE_PING_COLUMNS: [&str; 9] = [
+    "n",
+    "date",
+    "type",
+    "duration",
+    "exitstatus",
+    "rid",
+    "remote_addr",
+    "method",
+    "ua",
+];
+
+pub(crate) fn channels(client: &ManageClient, action: ChannelsAction, out: &Output) -> Result<()> {
+    match action {
+        ChannelsAction::List => out.print(&client.get_channels()?),
+        ChannelsAction::Assign(a) => assign_channel(client, &a, true, out),
+        ChannelsAction::Unassign(a) => assign_channel(client, &a, false, out),
+    }
+}
+
+/// Add the integration of `a` to the checks it names, or remove it when
+/// `assign` is false. Checks that already have it, or lack it, are left as
+/// they are.
+fn assign_channel(
+    client: &ManageClient,
+    a: &AssignChannel,
+    assign: bool,
+    out: &Output,
+) -> Result<()> {
+    let channel = find_channel(&client.get_channels()?, &a.channel)?;
+    let checks = resolve_checks(client, std::slice::from_ref(&a.check))?;
+    if let Some(check) = checks.iter().find(|check| check.channels.is_none()) {
+        return Err(eyre!(
+            "Check '{}' does not report its integrations, the API key might be read-only",
+            check.name
+        ));
+    }
+    let message = if assign {
+        format!(
+            "'{}' will be assigned to the following checks:",
+            channel.name
+        )
+    } else {
+        format!(
+            "'{}' will be unassigned from the following checks:",
+            channel.name
+        )
+    };
+    if checks.len() > 1 && !confirm(&message, &checks, a.yes)? {
+        return Ok(());
+    }
+    let updated = checks
+        .into_iter()
+        .map(|check| {
+            let mut ids = check.channel_ids();
+            if ids.contains(&channel.id) == assign {
+                return Ok(check);
+            }
+            if assign {
+                ids.push(channel.id.clone());
+            } else {
+                ids.retain(|id| *id != channel.id);
+            }
+            let changes = UpdatedCheck::builder()
+                .channels(Channels::from_ids(ids))
+                .build()?;
+            Ok(client.update_check(changes, &check_id(&check)?)?)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    out.print(&updated)
+}
+
+/// Find the integration with `term` as its UUID or name, or else the only one
+/// of the kind `term`.
+fn find_channel(channels: &[Channel], term: &str) -> Result<Channel> {
+    if let Some(channel) = channels.iter().find(|c| c.id == term || c.name == term) {
+        return Ok(channel.clone());
+    }
+    let describe = |channels: &[&Channel]| -> String {
+        channels
+            .iter()
+            .map(|c| format!("{} ({}, {})", c.name, c.kind, c.id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let of_kind: Vec<&Channel> = channels
+        .iter()
+        .filter(|c| c.kind.eq_ignore_ascii_case(term))
+        .collect();
+    match of_kind.as_slice() {
+        [channel] => Ok((*channel).clone()),
+        [] if channels.is_empty() => Err(eyre!("The project has no integrations")),
+        [] => Err(eyre!(
+            "No integration matched '{term}', available integrations: {}",
+            describe(&channels.iter().collect::<Vec<_>>())
+        )),
+        matched => Err(eyre!(
+            "'{term}' matched {} integrations, pass the UUID or name of one: {}",
+            matched.len(),
+            describe(matched)
+        )),
+    }
+}
+
+pub(crate) fn ping(
+    client: &ManageClient,
+    selection: &config::Selection,
+    ping: &cli::Ping,
+) -> Result<()> {
+    let check = single_check(client, &ping.check)?;
+    let url = check.ping_url.as_deref().ok_or_else(|| {
+        eyre!(
+            "Check '{}' has no ping URL, the API key might be read-only",
+            check.name
+        )
+    })?;
+    let kind = if ping.fail {
+        PingKind::Fail
+    } else if ping.start {
+        PingKind::Start
+    } else if ping.log {
+        PingKind::Log
+    } else if let Some(code) = ping.exit_code {
+        PingKind::ExitCode(code)
+    } else {
+        PingKind::Success
+    };
+    let body = match (&ping.body, &ping.body_file) {
+        // Logs are not always valid UTF-8, so invalid sequences are replaced
+        (Some(body), _) if body == "-" => {
+            let mut body = Vec::new();
+            io::stdin().read_to_end(&mut body)?;
+            Some(String::from_utf8_lossy(&body).into_owned())
+        }
+        (Some(body), _) => Some(body.clone()),
+        (None, Some(file)) => Some(String::from_utf8_lossy(&std::fs::read(file)?).into_owned()),
+        (None, None) => None,
+    };
+    if !selection
+        .ping_client(url)?
+        .send(kind, body.as_deref(), ping.rid.as_ref())
+    {
+        return Err(eyre!("Failed to ping '{}'", check.name));
+    }
+    eprintln!("Pinged '{}'", check.name);
+    Ok(())
+}
+
+pub(crate) fn pings(client: &ManageClient, p: &Pings, out: &Output) -> Result<()> {
+    let display = PingDisplay {
+        tz: if p.local {
+            TimeZone::system()
+        } else {
+            p.tz.clone().unwrap_or(TimeZone::UTC)
+        },
+        verbose: p.verbose,
+        body: p.body,
+    };
+    let out = ping_output(p, out);
+    let is_uuid = Uuid::parse_str(&p.check_id).is_ok();
+    if (is_uuid || p.follow) && !p.filter.is_empty() {
+        return Err(eyre!(
+            "Check filters only apply when searching checks by name, not with a UUID or --follow"
+        ));
+    }
+    if p.follow {
+        return follow_pings(client, p, &display, &out);
+    }
+    let mut pings = if is_uuid {
+        client
+            .list_logged_pings(&p.check_id)?
+            .into_iter()
+            .map(|ping| (p.check_id.clone(), ping))
+            .collect()
+    } else {
+        search_pings(client, &p.check_id, &p.filter)?
     };
+    select_pings(p, &mut pings);
+    out.print(&ping_records(client, pings, &display)?)
+}
+
+/// Print the latest pings of a check, then the pings it receives every
+/// interval until interrupted, or until a failure ping arrives with
+/// `--exit-on-fail`. Pings are told apart by their number, so that each is
+/// printed once.
+fn follow_pings(
+    client: &ManageClient,
+    p: &Pings,
+    display: &PingDisplay,
+    out: &Output,
+) -> Result<()> {
+    // JSON and YAML would print a separate document for every fetch
+    if matches!(out.format, OutputFormat::Json | OutputFormat::Yaml) {
+        return Err(eyre!(
+            "--follow prints one line per ping, use --output jsonl, csv, tsv or table"
+        ));
+    }
+    let check_id = check_id(&single_check(client, &p.check_id)?)?;
+    let pings = client.list_logged_pings(&check_id)?;
+    let mut last = pings.iter().map(|ping| ping.n).max().unwrap_or(0);
+    let mut latest: Vec<(String, Ping)> = pings
+        .into_iter()
+        .map(|ping| (check_id.clone(), ping))
+        .collect();
+    select_pings(p, &mut latest);
+    // Pings are listed newest first, but are followed in the order they arrive
+    latest.reverse();
+    out.print(&ping_records(client, latest, display)?)?;
+
+    let out = Output {
+        no_header: true,
+        ..out.clone()
+    };
+    loop {
+        thread::sleep(p.interval);
+        let pings = match client.list_logged_pings(&check_id) {
+            Ok(pings) => pings,
+            Err(err) => {
+                eprintln!("Failed to fetch the pings of '{}': {err}", p.check_id);
+                continue;
+            }
+        };
+        let mut new: Vec<(String, Ping)> = pings
+            .into_iter()
+            .filter(|ping| ping.n > last)
+            .map(|ping| (check_id.clone(), ping))
+            .collect();
+        new.sort_by_key(|(_, ping)| ping.n);
+        let Some(newest) = new.last().map(|(_, ping)| ping.n) else {
+            continue;
+        };
+        let failed = new.iter().any(|(_, ping)| ping.type_field == "fail");
+        if !p.kinds.is_empty() {
+            new.retain(|(_, ping)| p.kinds.contains(&ping.type_field));
+        }
+        if !new.is_empty() {
+            // Leave `last` alone so these pings are retried on the next poll
+            match ping_records(client, new, display) {
+                Ok(records) => out.print(&records)?,
+                Err(err) => {
+                    eprintln!("Failed to fetch the ping bodies of '{}': {err}", p.check_id);
+                    continue;
+                }
+            }
+        }
+        last = newest;
+        if let (true, Some(code)) = (failed, p.exit_on_fail) {
+            std::process::exit(code);
+        }
+    }
+}
+
+/// Output of `pings`, with the columns of `--verbose` and `--body` added to
+/// tables unless columns were picked.
+fn ping_output(p: &Pings, out: &Output) -> Output {
+    if out.format != OutputFormat::Table || !out.columns.is_empty() || !(p.verbose || p.body) {
+        return out.clone();
+    }
+    let mut columns: Vec<String> = if p.verbose {
+        VERBOSE_PING_COLUMNS.map(str::to_owned).to_vec()
+    } else {
+        PingRecord::columns()
+            .iter()
+            .map(|(key, _)| (*key).to_owned())
+            .collect()
+    };
+    if p.body {
+        columns.push("body".to_owned());
+    }
+    Output {
+        columns,
+        ..out.clone()
+    }
+}
+
+/// Keep the pings of the types and age picked in `p`, up to its limit.
+fn select_pings(p: &Pings, pings: &mut Vec<(String, Ping)>) {
+    if !p.kinds.is_empty() {
+        pings.retain(|(_, ping)| p.kinds.contains(&ping.type_field));
+    }
+    if let Some(since) = p.since {
+        pings.retain(|(_, ping)| {
+            ping.date
+                .parse::<Timestamp>()
+                .is_ok_and(|date| date >= since)
+        });
+    }
+    if p.limit > 0 {
+        pings.truncate(p.limit);
+    }
+}
+
+/// Prepare pings, paired with the UUIDs of their checks, for printing,
+/// fetching their bodies when those are shown.
+fn ping_records<'a>(
+    client: &ManageClient,
+    pings: Vec<(String, Ping)>,
+    display: &'a PingDisplay,
+) -> Result<Vec<PingRecord<'a>>> {
+    pings
+        .into_iter()
+        .map(|(check_id, ping)| {
+            let body = match ping.body_url {
+                Some(_) if display.body => Some(client.get_ping_body(&check_id, ping.n)?),
+                _ => None,
+            };
+            Ok(PingRecord {
+                ping,
+                body,
+                display,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn apply(
+    client: &ManageClient,
+    file: &Path,
+    prune: bool,
+    dry_run: bool,
+    out: &Output,
+) -> Result<()> {
+    let desired = DesiredChecks::from_toml_file(file)?;
+    let plan = Plan::fetch(client, &desired.checks, prune)?;
+    if out.format == OutputFormat::Table && out.columns.is_empty() {
+        print!("{plan}");
+    } else {
+        let changes: Vec<ChangeRecord<'_>> = plan.changes().iter().map(Into::into).collect();
+        out.print(&changes)?;
+    }
+    if dry_run || plan.is_empty() {
+        return Ok(());
+    }
+    plan.apply(client)?;
+    eprintln!("Applied {} change(s).", plan.changes().len());
+    Ok(())
+}
+
+pub(crate) fn export(
+    client: &ManageClient,
+    file: Option<&Path>,
+    format: Option<Format>,
+    out: &Output,
+) -> Result<()> {
+    let export = ProjectExport::new(
+        get_checks(client)?,
+        client.get_channels()?,
+        Timestamp::now().to_string(),
+    );
+    let format = match (format, file, out.format) {
+        (Some(format), _, _) => format,
+        (None, None, OutputFormat::Yaml) => Format::Yaml,
+        (None, file, _) => Format::from_path(file),
+    };
+    let output = export.serialize(format)?;
+    match file {
+        Some(file) => std::fs::write(file, output)?,
+        None => print!("{output}"),
+    }
+    Ok(())
+}
+
+pub(crate) fn import(
+    client: &ManageClient,
+    file: &Path,
+    format: Option<Format>,
+    out: &Output,
+) -> Result<()> {
+    let input = std::fs::read_to_string(file)?;
+    let export = ProjectExport::parse(
+        &input,
+        format.unwrap_or_else(|| Format::from_path(Some(file))),
+    )?;
+    let target = if export.channels.is_empty() {
+        Vec::new()
+    } else {
+        client.get_channels()?
+    };
+    let (channels, missing) = export.channel_map(&target);
+    for channel in missing {
+        eprintln!(
+            "warning: no {} channel named '{}' in this project, checks will not use it",
+            channel.kind, channel.name
+        );
+    }
+    let mut imported = Vec::with_capacity(export.checks.len());
+    for check in &export.checks {
+        let (result, created) = client.upsert_check(project::new_check(check, &channels))?;
+        if check.status == "paused" && created.status != "paused" {
+            if let Some(id) = created.id() {
+                client.pause(&id)?;
+            }
+        }
+        imported.push(ImportRecord {
+            id: created.id(),
+            name: &check.name,
+            result: match result {
+                UpsertResult::Created => "created",
+                UpsertResult::Updated => "updated",
+            },
+        });
+    }
+    out.print(&imported)
+}
+
+pub(crate) fn create(client: &ManageClient, create: Create, out: &Output) -> Result<()> {
+    let CheckFields {
+        tags,
+        desc,
+        timeout,
+        grace,
+        schedule,
+        tz,
+        manual_resume,
+        channels,
+    } = create.fields;
+    let mut builder = NewCheck::builder().name(&create.name);
+    if let Some(tags) = tags {
+        builder = builder.tags(tags.into_iter().filter(|tag| !tag.is_empty()));
+    }
+    if let Some(desc) = desc {
+        builder = builder.desc(&desc);
+    }
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(grace) = grace {
+        builder = builder.grace(grace);
+    }
+    if let Some(schedule) = schedule {
+        builder = builder.schedule(&schedule, tz.as_deref());
+    }
+    if let Some(manual_resume) = manual_resume {
+        builder = builder.manual_resume(manual_resume);
+    }
+    if let Some(channels) = channels {
+        builder = builder.channels(channels);
+    }
+    if !create.unique.is_empty() {
+        builder = builder.unique(create.unique);
+    }
+    let check = builder.build()?;
+    let check = if check.unique.is_some() {
+        let (result, check) = client.upsert_check(check)?;
+        if let UpsertResult::Updated = result {
+            eprintln!("A matching check already exists, updated it instead");
+        }
+        check
+    } else {
+        client.create_check(check)?
+    };
+    out.print(&[check])
+}
+
+pub(crate) fn update(client: &ManageClient, update: Update, out: &Output) -> Result<()> {
+    let CheckFields {
+        tags,
+        desc,
+        timeout,
+        grace,
+        schedule,
+        tz,
+        manual_resume,
+        channels,
+    } = update.fields;
+    let mut builder = UpdatedCheck::builder();
+    if let Some(name) = update.name {
+        builder = builder.name(&name);
+    }
+    if let Some(tags) = tags {
+        builder = builder.tags(tags.into_iter().filter(|tag| !tag.is_empty()));
+    }
+    if let Some(desc) = desc {
+        builder = builder.desc(&desc);
+    }
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(grace) = grace {
+        builder = builder.grace(grace);
+    }
+    if let Some(schedule) = schedule {
+        builder = builder.schedule(&schedule, tz.as_deref());
+    }
+    if let Some(manual_resume) = manual_resume {
+        builder = builder.manual_resume(manual_resume);
+    }
+    if let Some(channels) = channels {
+        builder = builder.channels(channels);
+    }
+    let changes = builder.build()?;
+    if serde_json::to_value(&changes)?
+        .as_object()
+        .is_some_and(Map::is_empty)
+    {
+        return Err(eyre!("No fields to update, see `hcctl update --help`"));
+    }
+    let checks = resolve_checks(client, std::slice::from_ref(&update.check))?;
+    if checks.len() > 1 && !confirm("The following checks will be updated:", &checks, update.yes)? {
+        return Ok(());
+    }
+    let updated = checks
+        .iter()
+        .map(|check| Ok(client.update_check(changes.clone(), &check_id(check)?)?))
+        .collect::<Result<Vec<_>>>()?;
+    out.print(&updated)
+}
+
+pub(crate) fn delete(client: &ManageClient, delete: &Delete, out: &Output) -> Result<()> {
+    let checks = resolve_checks(client, &delete.checks)?;
+    if !confirm("The following checks will be deleted:", &checks, delete.yes)? {
+        return Ok(());
+    }
+    let deleted = checks
+        .iter()
+        .map(|check| Ok(client.delete(&check_id(check)?)?))
+        .collect::<Result<Vec<_>>>()?;
+    out.print(&deleted)
+}
+
+pub(crate) fn pause(client: &ManageClient, pause: &Pause, out: &Output) -> Result<()> {
+    let checks = resolve_checks(client, &pause.checks)?;
+    if checks.len() > 1 && !confirm("The following checks will be paused:", &checks, pause.yes)? {
+        return Ok(());
+    }
+    let paused = checks
+        .iter()
+        .map(|check| Ok(client.pause(&check_id(check)?)?))
+        .collect::<Result<Vec<_>>>()?;
+    out.print(&paused)
+}
+
+pub(crate) fn stats(
+    client: &ManageClient,
+    check: &str,
+    window: Duration,
+    out: &Output,
+) -> Result<()> {
+    let end = Timestamp::now();
+    let start = end - SignedDuration::try_from(window)?;
+    let records = resolve_checks(client, &[check.to_owned()])?
+        .iter()
+        .map(|check| {
+            let id = check_id(check)?;
+            let uptime = Uptime::from_flips(&client.list_status_changes(&id)?, start, end)?;
+            let runs = RunDurations::from_pings(&client.list_logged_pings(&id)?)?;
+            let seconds = |duration: Option<SignedDuration>| duration.map(|d| d.as_secs_f64());
+            Ok(StatsRecord {
+                id,
+                name: check.name.clone(),
+                uptime_percent: uptime.percentage(),
+                outages: uptime.outages.len(),
+                downtime_seconds: uptime.downtime().as_secs_f64(),
+                longest_outage_seconds: seconds(uptime.longest_outage()),
+                mttr_seconds: seconds(uptime.mttr()),
+                runs: runs.len(),
+                p50_seconds: seconds(runs.percentile(50.0)),
+                p95_seconds: seconds(runs.percentile(95.0)),
+                max_seconds: seconds(runs.max()),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    out.print(&records)
+}
+
+pub(crate) fn list(client: &ManageClient, filter: &CheckFilter, out: &Output) -> Result<()> {
+    let mut checks = get_checks(client)?;
+    filter.apply(&mut checks, |check| check);
+    out.print(&checks)
+}
+
+pub(crate) fn search(
+    client: &ManageClient,
+    search_term: &str,
+    filter: &CheckFilter,
+    out: &Output,
+) -> Result<()> {
+    out.print(&search_checks(client, search_term, filter)?)
+}
 
-    print_pings(pings)
+pub(crate) fn list_all_profiles(filter: &CheckFilter, out: &Output) -> Result<()> {
+    let mut checks = get_checks_of_all_profiles()?;
+    filter.apply(&mut checks, |c| &c.check);
+    out.print(&checks)
 }
 
-pub(crate) fn list(settings: Settings) -> Result<()> {
-    let client = manage::get_client(settings.token, settings.ua)?;
-    print_checks(client.get_checks()?)
+pub(crate) fn search_all_profiles(
+    search_term: &str,
+    filter: &CheckFilter,
+    out: &Output,
+) -> Result<()> {
+    let mut checks = get_checks_of_all_profiles()?;
+    checks.retain(|c| matches_search(&c.check, search_term));
+    filter.apply(&mut checks, |c| &c.check);
+    if checks.is_empty() {
+        return Err(eyre!("No checks matched search term '{}'", search_term));
+    }
+    out.print(&checks)
 }
 
-pub(crate) fn search(settings: Settings, search_term: &str) -> Result<()> {
-    let client = manage::get_client(settings.token, settings.ua)?;
+/// Fetch the checks of every profile in parallel. Profiles that fail are
+/// reported and left out, so that one bad key does not hide the other
+/// projects, unless every profile fails.
+fn get_checks_of_all_profiles() -> Result<Vec<ProjectCheck>> {
+    let profiles = config::all_profiles()?;
+    let results: Vec<Result<Vec<Check>>> = thread::scope(|scope| {
+        let handles: Vec<_> = profiles
+            .iter()
+            .map(|selection| scope.spawn(move || Ok(selection.client()?.get_checks()?)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err(eyre!("fetching the checks panicked")))
+            })
+            .collect()
+    });
 
-    match search_checks(&client, search_term) {
-        Ok(checks) => print_checks(checks),
-        Err(error) => Err(error),
+    let mut checks = Vec::new();
+    let mut failed = 0;
+    for (selection, result) in profiles.iter().zip(results) {
+        let project = selection.name.as_deref().unwrap_or_default();
+        match result {
+            Ok(project_checks) => {
+                checks.extend(project_checks.into_iter().map(|check| ProjectCheck {
+                    project: project.to_owned(),
+                    check,
+                }))
+            }
+            Err(error) => {
+                eprintln!("Failed to fetch the checks of profile '{project}': {error}");
+                failed += 1;
+            }
+        }
+    }
+    if failed == profiles.len() {
+        return Err(eyre!("Failed to fetch the checks of every profile"));
     }
+    Ok(checks)
 }
 
-fn search_pings(client: &ManageClient, search_term: &str) -> Result<Vec<Ping>> {
-    let pings: Vec<Ping> = search_checks(client, search_term)?
+/// Pings of the checks matching `search_term`, paired with the UUIDs of their checks.
+fn search_pings(
+    client: &ManageClient,
+    search_term: &str,
+    filter: &CheckFilter,
+) -> Result<Vec<(String, Ping)>> {
+    let pings: Vec<(String, Ping)> = search_checks(client, search_term, filter)?
         .iter()
-        .filter_map(|check| client.list_logged_pings(check.id()?.as_str()).ok())
+        .filter_map(|check| {
+            let id = check.id()?;
+            let pings = client.list_logged_pings(&id).ok()?;
+            Some(pings.into_iter().map(move |ping| (id.clone(), ping)))
+        })
         .flatten()
         .collect();
 
@@ -48,16 +694,117 @@ fn search_pings(client: &ManageClient, search_term: &str) -> Result<Vec<Ping>> {
     }
 }
 
-fn search_checks(client: &ManageClient, search_term: &str) -> Result<Vec<Check>> {
-    let checks: Vec<Check> = client
-        .get_checks()?
+/// Find the checks for each of `terms`, which are either UUIDs, exact names or
+/// search terms matched against the names of all checks.
+fn resolve_checks(client: &ManageClient, terms: &[String]) -> Result<Vec<Check>> {
+    let mut all_checks = None;
+    let mut resolved: Vec<Check> = Vec::new();
+    for term in terms {
+        let checks = if Uuid::parse_str(term).is_ok() {
+            vec![client.get_check(term)?]
+        } else {
+            let all_checks = match all_checks {
+                Some(ref checks) => checks,
+                None => all_checks.insert(get_checks(client)?),
+            };
+            let exact: Vec<Check> = all_checks
+                .iter()
+                .filter(|check| check.name == *term)
+                .cloned()
+                .collect();
+            if exact.is_empty() {
+                filter_checks(all_checks.clone(), term)?
+            } else {
+                exact
+            }
+        };
+        for check in checks {
+            if !resolved.iter().any(|c| c.id() == check.id()) {
+                resolved.push(check);
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+/// Find the check for `term` like [`resolve_checks`], when it matches only one.
+fn single_check(client: &ManageClient, term: &str) -> Result<Check> {
+    let mut checks = resolve_checks(client, &[term.to_owned()])?;
+    if checks.len() > 1 {
+        return Err(eyre!(
+            "'{term}' matched {} checks, pass the UUID or exact name of one",
+            checks.len()
+        ));
+    }
+    Ok(checks.remove(0))
+}
+
+/// Fetch the checks of the project, refreshing the cache used by completions.
+fn get_checks(client: &ManageClient) -> Result<Vec<Check>> {
+    let checks = client.get_checks()?;
+    cache::store_checks(&checks);
+    Ok(checks)
+}
+
+fn check_id(check: &Check) -> Result<String> {
+    check.id().ok_or_else(|| {
+        eyre!(
+            "Check '{}' has no ID, the API key might be read-only",
+            check.name
+        )
+    })
+}
+
+/// Ask whether to go ahead with an action on `checks`, unless `yes` is set.
+fn confirm(message: &str, checks: &[Check], yes: bool) -> Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+    if !io::stdin().is_terminal() {
+        return Err(eyre!(
+            "Refusing to change {} check(s) without --yes when standard input is not a terminal",
+            checks.len()
+        ));
+    }
+    eprintln!("{message}");
+    for check in checks {
+        eprintln!(
+            "  {} ({})",
+            check.name,
+            check.id().as_deref().unwrap_or("-")
+        );
+    }
+    eprint!("Continue? [y/N] ");
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    let confirmed = matches!(answer.trim().to_lowercase().as_str(), "y" | "yes");
+    if !confirmed {
+        eprintln!("Aborted");
+    }
+    Ok(confirmed)
+}
+
+fn search_checks(
+    client: &ManageClient,
+    search_term: &str,
+    filter: &CheckFilter,
+) -> Result<Vec<Check>> {
+    let mut checks = filter_checks(get_checks(client)?, search_term)?;
+    filter.apply(&mut checks, |check| check);
+    if checks.is_empty() {
+        return Err(eyre!(
+            "No checks matched search term '{}' and the filters",
+            search_term
+        ));
+    }
+    Ok(checks)
+}
+
+fn filter_checks(checks: Vec<Check>, search_term: &str) -> Result<Vec<Check>> {
+    let checks: Vec<Check> = checks
         .into_iter()
-        .filter(|check| {
-            check
-                .name
-                .to_lowercase()
-                .contains(&search_term.to_lowercase())
-        })
+        .filter(|check| matches_search(check, search_term))
         .collect();
     if checks.is_empty() {
         Err(eyre!("No checks matched search term '{}'", search_term))
@@ -66,93 +813,418 @@ fn search_checks(client: &ManageClient, search_term: &str) -> Result<Vec<Check>>
     }
 }
 
-fn print_pings(mut pings: Vec<Ping>) -> Result<()> {
-    let mut table = Table::new();
-    table
-        .load_preset(UTF8_FULL)
-        .apply_modifier(UTF8_ROUND_CORNERS)
-        .set_content_arrangement(ContentArrangement::Dynamic)
-        .set_header(vec!["Number", "Time", "Type", "Duration"]);
-    pings.truncate(10);
-    for ping in pings {
-        let utc_time = OffsetDateTime::parse(&ping.date, &Rfc3339)?.to_offset(UtcOffset::UTC);
-        let date = utc_time.date();
-        let time = utc_time.time();
-        let time_str = format!(
-            "{}/{} {}:{}",
-            date.day(),
-            date.month(),
-            time.hour(),
-            time.minute(),
-        );
-        let duration_str = if let Some(duration) = ping.duration {
-            format!("{duration:.3} sec")
-        } else {
-            "-".to_owned()
+fn matches_search(check: &Check, search_term: &str) -> bool {
+    check
+        .name
+        .to_lowercase()
+        .contains(&search_term.to_lowercase())
+}
+
+/// How `pings` shows the times of pings in tables.
+struct PingDisplay {
+    tz: TimeZone,
+    /// Show full timestamps rather than just the day and time.
+    verbose: bool,
+    /// Include the bodies of pings, which are fetched separately.
+    body: bool,
+}
+
+/// A ping printed by `pings`.
+#[derive(Serialize)]
+struct PingRecord<'a> {
+    #[serde(flatten)]
+    ping: Ping,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    #[serde(skip)]
+    display: &'a PingDisplay,
+}
+
+impl Record for PingRecord<'_> {
+    fn columns() -> &'static [(&'static str, &'static str)] {
+        &[
+            ("n", "Number"),
+            ("date", "Time"),
+            ("type", "Type"),
+            ("duration", "Duration"),
+        ]
+    }
+
+    fn headers() -> &'static [(&'static str, &'static str)] {
+        &[
+            ("exitstatus", "Exit Status"),
+            ("rid", "Run ID"),
+            ("remote_addr", "Remote Address"),
+            ("method", "Method"),
+            ("ua", "User Agent"),
+            ("scheme", "Scheme"),
+            ("body", "Body"),
+        ]
+    }
+
+    fn fields(&self) -> Result<Map<String, Value>> {
+        let mut fields = match serde_json::to_value(&self.ping)? {
+            Value::Object(fields) => fields,
+            value => return Err(eyre!("expected an object, found {value}")),
         };
-        table.add_row(vec![
-            format!("#{}", ping.n),
-            time_str,
-            ping.type_field,
-            duration_str,
-        ]);
+        // Optional fields are left out of pings that do not have them, but
+        // should still be selectable as columns
+        for key in ["duration", "rid", "exitstatus"] {
+            fields.entry(key).or_insert(Value::Null);
+        }
+        if self.display.body {
+            fields.insert("body".to_owned(), self.body.clone().into());
+        }
+        Ok(fields)
     }
 
-    println!("{table}");
-    Ok(())
+    fn table_cell(&self, key: &str, value: &Value) -> Result<String> {
+        Ok(match (key, value) {
+            ("n", _) => format!("#{}", self.ping.n),
+            ("date", _) => {
+                let date = self
+                    .ping
+                    .date
+                    .parse::<Timestamp>()?
+                    .to_zoned(self.display.tz.clone());
+                if self.display.verbose {
+                    date.strftime("%Y-%m-%dT%H:%M:%S%:z").to_string()
+                } else {
+                    date.strftime("%d/%m %H:%M").to_string()
+                }
+            }
+            ("duration", _) => match self.ping.duration {
+                Some(duration) => format!("{duration:.3} sec"),
+                None => "-".to_owned(),
+            },
+            ("body", Value::String(body)) => body.trim_end().to_owned(),
+            (_, Value::Null) => "-".to_owned(),
+            (_, value) => output::cell(value),
+        })
+    }
 }
 
-fn print_checks(checks: Vec<Check>) -> Result<()> {
-    let mut table = Table::new();
-    table
-        .load_preset(UTF8_FULL)
-        .apply_modifier(UTF8_ROUND_CORNERS)
-        .set_content_arrangement(ContentArrangement::Dynamic)
-        .set_header(vec!["ID", "Name", "Last Ping"]);
-    let now = OffsetDateTime::now_utc();
-    for check in checks {
-        let date = if let Some(ref date_str) = check.last_ping {
-            human_readable_duration(now, date_str)?
-        } else {
-            "-".to_owned()
+impl Record for Channel {
+    fn columns() -> &'static [(&'static str, &'static str)] {
+        &[("id", "ID"), ("name", "Name"), ("kind", "Kind")]
+    }
+}
+
+impl Record for Check {
+    fn columns() -> &'static [(&'static str, &'static str)] {
+        &[("id", "ID"), ("name", "Name"), ("last_ping", "Last Ping")]
+    }
+
+    fn fields(&self) -> Result<Map<String, Value>> {
+        let mut fields = Map::new();
+        fields.insert("id".to_owned(), self.id().into());
+        match serde_json::to_value(self)? {
+            Value::Object(check) => fields.extend(check),
+            value => return Err(eyre!("expected an object, found {value}")),
+        }
+        Ok(fields)
+    }
+
+    fn table_cell(&self, key: &str, value: &Value) -> Result<String> {
+        Ok(match (key, value) {
+            ("last_ping", Value::String(date_str)) => {
+                human_readable_duration(Timestamp::now(), date_str)?
+            }
+            (_, Value::Null) => "-".to_owned(),
+            (_, value) => output::cell(value),
+        })
+    }
+}
+
+/// A check listed across profiles, along with the profile it belongs to.
+#[derive(Serialize)]
+struct ProjectCheck {
+    project: String,
+    #[serde(flatten)]
+    check: Check,
+}
+
+impl Record for ProjectCheck {
+    fn columns() -> &'static [(&'static str, &'static str)] {
+        &[
+            ("project", "Project"),
+            ("id", "ID"),
+            ("name", "Name"),
+            ("last_ping", "Last Ping"),
+        ]
+    }
+
+    fn fields(&self) -> Result<Map<String, Value>> {
+        let mut fields = Map::new();
+        fields.insert("project".to_owned(), self.project.clone().into());
+        fields.extend(self.check.fields()?);
+        Ok(fields)
+    }
+
+    fn table_cell(&self, key: &str, value: &Value) -> Result<String> {
+        self.check.table_cell(key, value)
+    }
+}
+
+/// A planned change printed by `apply` in machine readable formats.
+#[derive(Serialize)]
+struct ChangeRecord<'a> {
+    action: &'static str,
+    id: Option<&'a str>,
+    name: &'a str,
+    fields: Vec<FieldRecord<'a>>,
+}
+
+#[derive(Serialize)]
+struct FieldRecord<'a> {
+    field: &'static str,
+    old: &'a str,
+    new: &'a str,
+}
+
+impl<'a> From<&'a Change> for ChangeRecord<'a> {
+    fn from(change: &'a Change) -> Self {
+        let (action, id, name, fields) = match change {
+            Change::Create { name, fields, .. } => ("create", None, name, fields.as_slice()),
+            Change::Update {
+                id, name, fields, ..
+            } => ("update", Some(id.as_str()), name, fields.as_slice()),
+            Change::Delete { id, name } => ("delete", Some(id.as_str()), name, &[][..]),
         };
-        let id = check.id().unwrap_or_else(|| "-".to_owned());
-        table.add_row(vec![id, check.name, date]);
+        ChangeRecord {
+            action,
+            id,
+            name,
+            fields: fields
+                .iter()
+                .map(|f| FieldRecord {
+                    field: f.field,
+                    old: &f.old,
+                    new: &f.new,
+                })
+                .collect(),
+        }
     }
+}
 
-    println!("{table}");
-    Ok(())
+impl Record for ChangeRecord<'_> {
+    fn columns() -> &'static [(&'static str, &'static str)] {
+        &[("action", "Action"), ("id", "ID"), ("name", "Name")]
+    }
+}
+
+/// Statistics of a check printed by `stats`.
+#[derive(Serialize)]
+struct StatsRecord {
+    id: String,
+    name: String,
+    uptime_percent: f64,
+    outages: usize,
+    downtime_seconds: f64,
+    longest_outage_seconds: Option<f64>,
+    mttr_seconds: Option<f64>,
+    runs: usize,
+    p50_seconds: Option<f64>,
+    p95_seconds: Option<f64>,
+    max_seconds: Option<f64>,
+}
+
+impl Record for StatsRecord {
+    fn columns() -> &'static [(&'static str, &'static str)] {
+        &[
+            ("name", "Name"),
+            ("uptime_percent", "Uptime"),
+            ("outages", "Outages"),
+            ("downtime_seconds", "Downtime"),
+            ("mttr_seconds", "MTTR"),
+            ("runs", "Runs"),
+            ("p50_seconds", "p50"),
+            ("p95_seconds", "p95"),
+            ("max_seconds", "Max"),
+        ]
+    }
+
+    fn table_cell(&self, key: &str, value: &Value) -> Result<String> {
+        Ok(match (key, value.as_f64()) {
+            (_, None) if value.is_null() => "-".to_owned(),
+            ("uptime_percent", Some(percent)) => format!("{percent:.3}%"),
+            (key, Some(seconds)) if key.ends_with("_seconds") => {
+                if seconds < 60.0 {
+                    format!("{seconds:.1}s")
+                } else {
+                    // Whole seconds are precise enough for anything over a minute
+                    compact_duration(seconds.round() as i64)
+                }
+            }
+            _ => output::cell(value),
+        })
+    }
 }
 
-fn human_readable_duration(now: OffsetDateTime, date_str: &str) -> Result<String> {
-    let date = OffsetDateTime::parse(date_str, &Rfc3339)?;
-    let duration = now - date;
-    let hours = duration.whole_hours();
+/// A check created or updated by `import`.
+#[derive(Serialize)]
+struct ImportRecord<'a> {
+    id: Option<String>,
+    name: &'a str,
+    result: &'static str,
+}
+
+impl Record for ImportRecord<'_> {
+    fn columns() -> &'static [(&'static str, &'static str)] {
+        &[("id", "ID"), ("name", "Name"), ("result", "Result")]
+    }
+}
+
+fn human_readable_duration(now: Timestamp, date_str: &str) -> Result<String> {
+    let date: Timestamp = date_str.parse()?;
+    let duration = now.duration_since(date);
+    let hours = duration.as_hours();
     let minutes = if hours == 0 {
-        duration.whole_minutes()
+        duration.as_mins()
     } else {
-        duration.whole_minutes() % hours
+        duration.as_mins() % hours
     };
     Ok(format!("{hours} hour(s) and {minutes} minute(s) ago"))
 }
 
 #[cfg(test)]
 mod tests {
-    use time::macros::datetime;
-
+    use super::{find_channel, PingDisplay, PingRecord};
     use crate::cmds::human_readable_duration;
+    use crate::output::{Output, OutputFormat, Record};
+    use crate::test_utils::check;
+    use healthchecks::model::{Channel, Ping};
+    use jiff::tz::TimeZone;
+    use jiff::Timestamp;
 
     #[test]
     fn duration_parses_correctly() {
-        let now = datetime!(2021-01-26 19:38:00 UTC);
+        let now: Timestamp = "2021-01-26T19:38:00Z".parse().unwrap();
         let duration = human_readable_duration(now, "2021-01-26T14:00:24+00:00").unwrap();
         assert_eq!(duration, "5 hour(s) and 2 minute(s) ago");
     }
 
     #[test]
     fn duration_parses_correctly_with_only_minutes() {
-        let now = datetime!(2021-01-26 14:38:00 UTC);
+        let now: Timestamp = "2021-01-26T14:38:00Z".parse().unwrap();
         let duration = human_readable_duration(now, "2021-01-26T14:00:24+00:00").unwrap();
         assert_eq!(duration, "0 hour(s) and 37 minute(s) ago");
     }
+
+    #[test]
+    fn checks_include_their_id() {
+        let mut check = check("backup", "prod", "new", None);
+        check.ping_url =
+            Some("https://hc-ping.com/5b8e4f5e-fc5d-4a2b-8c6e-6b9a8a1c2d3e".to_owned());
+        let output = Output {
+            format: OutputFormat::Csv,
+            columns: vec!["id".to_owned(), "name".to_owned(), "last_ping".to_owned()],
+            no_header: false,
+        };
+        assert_eq!(
+            output.render(std::slice::from_ref(&check)).unwrap(),
+            "id,name,last_ping\n5b8e4f5e-fc5d-4a2b-8c6e-6b9a8a1c2d3e,backup,\n"
+        );
+        let project_check = super::ProjectCheck {
+            project: "prod".to_owned(),
+            check,
+        };
+        let output = Output {
+            columns: vec!["project".to_owned(), "id".to_owned()],
+            ..output
+        };
+        assert_eq!(
+            output.render(&[project_check]).unwrap(),
+            "project,id\nprod,5b8e4f5e-fc5d-4a2b-8c6e-6b9a8a1c2d3e\n"
+        );
+    }
+
+    #[test]
+    fn finds_channels_by_id_name_or_kind() {
+        let channel = |id: &str, name: &str, kind: &str| Channel {
+            id: id.to_owned(),
+            name: name.to_owned(),
+            kind: kind.to_owned(),
+        };
+        let channels = [
+            channel("4ec5a071", "Ops", "email"),
+            channel("746a083e", "Oncall", "email"),
+            channel("b1f2c3d4", "Chat", "slack"),
+        ];
+        let find = |term: &str| find_channel(&channels, term).map(|c| c.id);
+        assert_eq!(find("746a083e").unwrap(), "746a083e");
+        assert_eq!(find("Ops").unwrap(), "4ec5a071");
+        assert_eq!(find("slack").unwrap(), "b1f2c3d4");
+        assert!(find("email")
+            .unwrap_err()
+            .to_string()
+            .starts_with("'email' matched 2 integrations"));
+        assert!(find("sms").is_err());
+        assert_eq!(
+            find_channel(&[], "Ops").unwrap_err().to_string(),
+            "The project has no integrations"
+        );
+    }
+
+    #[test]
+    fn pings_show_times_in_the_display_timezone() {
+        let ping: Ping = serde_json::from_value(serde_json::json!({
+            "type": "fail",
+            "date": "2024-01-01T23:30:00+00:00",
+            "n": 7,
+            "scheme": "http",
+            "remote_addr": "127.0.0.1",
+            "method": "POST",
+            "ua": "curl/8.0",
+            "exitstatus": 3,
+        }))
+        .unwrap();
+        let display = PingDisplay {
+            tz: TimeZone::get("Europe/Riga").unwrap(),
+            verbose: false,
+            body: false,
+        };
+        let record = PingRecord {
+            ping,
+            body: None,
+            display: &display,
+        };
+        let fields = record.fields().unwrap();
+        assert_eq!(
+            record.table_cell("date", &fields["date"]).unwrap(),
+            "02/01 01:30"
+        );
+        assert_eq!(record.table_cell("rid", &fields["rid"]).unwrap(), "-");
+        assert_eq!(
+            record
+                .table_cell("exitstatus", &fields["exitstatus"])
+                .unwrap(),
+            "3"
+        );
+        let verbose = PingDisplay {
+            tz: TimeZone::UTC,
+            verbose: true,
+            body: true,
+        };
+        let record = PingRecord {
+            body: Some("disk full\n".to_owned()),
+            display: &verbose,
+            ..record
+        };
+        assert_eq!(
+            record.table_cell("date", &fields["date"]).unwrap(),
+            "2024-01-01T23:30:00+00:00"
+        );
+        let fields = record.fields().unwrap();
+        assert_eq!(
+            record.table_cell("body", &fields["body"]).unwrap(),
+            "disk full"
+        );
+        let output = Output {
+            format: OutputFormat::Csv,
+            columns: vec!["n".to_owned(), "ua".to_owned(), "rid".to_owned()],
+            no_header: false,
+        };
+        assert_eq!(output.render(&[record]).unwrap(), "n,ua,rid\n7,curl/8.0,\n");
+    }
 }
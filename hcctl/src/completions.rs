@@ -0,0 +1,156 @@
+use crate::cache;
+use crate::cli::Opts;
+use crate::config::{self, ConfigFile};
+use clap::builder::StyledStr;
+use clap::CommandFactory;
+use clap_complete::engine::CompletionCandidate;
+use clap_complete::env::{CompleteEnv, Shells};
+use clap_complete::Shell;
+use color_eyre::{eyre::eyre, Result};
+use healthchecks::model::Check;
+use std::path::Path;
+use std::time::Duration;
+
+/// Environment variable the registered scripts set to ask hcctl for completions.
+const COMPLETE_VAR: &str = "COMPLETE";
+
+/// How long cached checks are offered as completions before fetching them again.
+const CACHE_MAX_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// Answer the completion request of a registered shell script and exit, if
+/// this run is one.
+pub(crate) fn complete() {
+    CompleteEnv::with_factory(Opts::command)
+        .var(COMPLETE_VAR)
+        .complete();
+}
+
+/// Print the script that registers the completions of hcctl for `shell`.
+pub(crate) fn print_registration(shell: Shell) -> Result<()> {
+    let name = shell.to_string();
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&name)
+        .ok_or_else(|| eyre!("completions are not supported for {name}"))?;
+    completer.write_registration(
+        COMPLETE_VAR,
+        "hcctl",
+        "hcctl",
+        "hcctl",
+        &mut std::io::stdout(),
+    )?;
+    Ok(())
+}
+
+/// Write the man pages of hcctl and its subcommands to `dir`.
+pub(crate) fn write_man_pages(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    clap_mangen::generate_to(Opts::command(), dir)?;
+    Ok(())
+}
+
+/// Profile passed on the command line being completed, as completions are
+/// offered before the arguments are parsed.
+fn profile_arg() -> Option<String> {
+    // The command line follows the first `--`
+    let mut args = std::env::args().skip_while(|arg| arg != "--").skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_owned());
+        }
+    }
+    None
+}
+
+/// Names of the profiles in the configuration file.
+pub(crate) fn profile_candidates() -> Vec<CompletionCandidate> {
+    let file = config::config_path()
+        .and_then(|path| ConfigFile::load(&path).ok())
+        .unwrap_or_default();
+    file.profiles
+        .into_keys()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// Names and UUIDs of the checks in the project, from the cache when it is
+/// recent enough. Completions cannot report errors, so none are offered when
+/// the checks cannot be fetched.
+pub(crate) fn check_candidates() -> Vec<CompletionCandidate> {
+    let Ok(selection) = config::select(profile_arg().as_deref()) else {
+        return Vec::new();
+    };
+    cache::use_profile(selection.name.as_deref());
+    let checks = cache::load_checks(CACHE_MAX_AGE).or_else(|| {
+        let client = selection.client().ok()?.set_timeout(Duration::from_secs(5));
+        let checks = client.get_checks().ok()?;
+        cache::store_checks(&checks);
+        Some(checks)
+    });
+    candidates(checks.unwrap_or_default())
+}
+
+/// Offer each check by UUID, described by its name, and by name, described by
+/// its UUID.
+fn candidates(checks: Vec<Check>) -> Vec<CompletionCandidate> {
+    let mut candidates = Vec::new();
+    for check in checks {
+        if let Some(id) = check.id() {
+            candidates.push(
+                CompletionCandidate::new(&id).help(Some(StyledStr::from(check.name.clone()))),
+            );
+            candidates.push(CompletionCandidate::new(&check.name).help(Some(StyledStr::from(id))));
+        } else {
+            candidates.push(CompletionCandidate::new(&check.name));
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offers_names_and_uuids() {
+        let check = |name: &str, ping_url: Option<&str>| -> Check {
+            let mut check = crate::test_utils::check(name, "", "up", None);
+            check.unique_key = None;
+            check.ping_url = ping_url.map(ToOwned::to_owned);
+            check
+        };
+        let candidates = candidates(vec![
+            check(
+                "backup",
+                Some("https://hc-ping.com/5b8e4f5e-fc5d-4a2b-8c6e-6b9a8a1c2d3e"),
+            ),
+            check("read-only", None),
+        ]);
+        let values: Vec<(String, Option<String>)> = candidates
+            .iter()
+            .map(|c| {
+                (
+                    c.get_value().to_string_lossy().into_owned(),
+                    c.get_help().map(ToString::to_string),
+                )
+            })
+            .collect();
+        assert_eq!(
+            values,
+            [
+                (
+                    "5b8e4f5e-fc5d-4a2b-8c6e-6b9a8a1c2d3e".to_owned(),
+                    Some("backup".to_owned())
+                ),
+                (
+                    "backup".to_owned(),
+                    Some("5b8e4f5e-fc5d-4a2b-8c6e-6b9a8a1c2d3e".to_owned())
+                ),
+                ("read-only".to_owned(), None),
+            ]
+        );
+    }
+}
@@ -0,0 +1,467 @@
+use crate::cli::{Config, ConfigAction, ProfileField, SetProfile};
+use crate::output::{Output, OutputFormat, Record};
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use healthchecks::env::{HEALTHCHECKS_TIMEOUT, HEALTHCHECKS_TOKEN, HEALTHCHECKS_USERAGENT};
+use healthchecks::manage::{get_client, get_client_with_url, ManageClient};
+use healthchecks::ping::PingClient;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::env::var;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Environment variable selecting the profile when `--profile` is not passed.
+pub(crate) const HCCTL_PROFILE: &str = "HCCTL_PROFILE";
+
+/// Environment variable overriding the location of the configuration file.
+pub(crate) const HCCTL_CONFIG: &str = "HCCTL_CONFIG";
+
+/// Base directory of the given XDG kind, such as `XDG_CONFIG_HOME`, falling back
+/// to `fallback` within the home directory.
+pub(crate) fn xdg_dir(name: &str, fallback: &str) -> Option<PathBuf> {
+    var(name)
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(fallback))
+        })
+}
+
+/// Location of the configuration file, if there is a home directory to put it in.
+pub(crate) fn config_path() -> Option<PathBuf> {
+    var(HCCTL_CONFIG)
+        .ok()
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| Some(xdg_dir("XDG_CONFIG_HOME", ".config")?.join("hcctl/config.toml")))
+}
+
+/// Configuration file of hcctl, with the profiles of the projects it manages.
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ConfigFile {
+    /// Profile used when none is selected and `HEALTHCHECKS_TOKEN` is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) default_profile: Option<String>,
+    #[serde(default)]
+    pub(crate) profiles: BTreeMap<String, Profile>,
+}
+
+/// How to connect to a project, and the defaults used when managing it.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) api_key: Option<String>,
+    /// Command printing the API key, such as a call to a secret manager.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) api_key_command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) api_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) user_agent: Option<String>,
+    /// Timeout for every request, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) timeout: Option<u64>,
+    /// Format used when `--output` is not passed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) output: Option<OutputFormat>,
+    /// Columns used when `--columns` is not passed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) columns: Option<Vec<String>>,
+}
+
+impl ConfigFile {
+    /// Read the configuration file at `path`, which is empty if it does not exist.
+    pub(crate) fn load(path: &Path) -> Result<ConfigFile> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents)
+                .wrap_err_with(|| format!("Failed to parse {}", path.display())),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(ConfigFile::default()),
+            Err(error) => Err(error).wrap_err_with(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Write the configuration file to `path`, readable only by the current
+    /// user as it can contain API keys.
+    pub(crate) fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(path)
+            .wrap_err_with(|| format!("Failed to write {}", path.display()))?;
+        io::Write::write_all(&mut file, toml::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Pick the profile named `name`, or the default profile unless hcctl is
+    /// configured from the environment because `token_set` is.
+    pub(crate) fn select(&self, name: Option<&str>, token_set: bool) -> Result<Selection> {
+        let name = match name {
+            Some(name) => name,
+            None if token_set => return Ok(Selection::default()),
+            None => match self.default_profile {
+                Some(ref name) => name,
+                None => return Ok(Selection::default()),
+            },
+        };
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| self.unknown_profile(name))?;
+        Ok(Selection {
+            name: Some(name.to_owned()),
+            profile: profile.clone(),
+        })
+    }
+
+    fn unknown_profile(&self, name: &str) -> color_eyre::Report {
+        if self.profiles.is_empty() {
+            eyre!("No profile named '{name}', add one with `hcctl config set {name}`")
+        } else {
+            let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            eyre!(
+                "No profile named '{name}', available profiles: {}",
+                names.join(", ")
+            )
+        }
+    }
+}
+
+/// The profile hcctl runs with, which is none when it is configured from the
+/// environment.
+#[derive(Debug, Default)]
+pub(crate) struct Selection {
+    pub(crate) name: Option<String>,
+    pub(crate) profile: Profile,
+}
+
+impl Selection {
+    /// Create a client for the project of the profile, or from the environment
+    /// when there is none.
+    pub(crate) fn client(&self) -> Result<ManageClient> {
+        let Some(ref name) = self.name else {
+            return Ok(ManageClient::from_env()?);
+        };
+        let profile = &self.profile;
+        let api_key = match (&profile.api_key, &profile.api_key_command) {
+            (Some(_), Some(_)) => {
+                return Err(eyre!(
+                    "Profile '{name}' sets both api_key and api_key_command"
+                ))
+            }
+            (Some(api_key), None) => api_key.clone(),
+            (None, Some(command)) => run_key_command(name, command)?,
+            (None, None) => {
+                return Err(eyre!("Profile '{name}' has no api_key or api_key_command"))
+            }
+        };
+        let user_agent = profile.user_agent.clone();
+        let mut client = match profile.api_url {
+            Some(ref api_url) => get_client_with_url(api_key, user_agent, api_url.clone())?,
+            None => get_client(api_key, user_agent)?,
+        };
+        if let Some(timeout) = profile.timeout {
+            client = client.set_timeout(Duration::from_secs(timeout));
+        }
+        Ok(client)
+    }
+
+    /// Create a client pinging the check at `url`, with the user agent and
+    /// timeout of the profile, or of the environment when there is none.
+    pub(crate) fn ping_client(&self, url: &str) -> Result<PingClient> {
+        let (user_agent, timeout) = if self.name.is_some() {
+            (self.profile.user_agent.clone(), self.profile.timeout)
+        } else {
+            let timeout = match var(HEALTHCHECKS_TIMEOUT).ok().filter(|t| !t.is_empty()) {
+                Some(timeout) => Some(timeout.parse().wrap_err_with(|| {
+                    format!("{HEALTHCHECKS_TIMEOUT} must be a number of seconds")
+                })?),
+                None => None,
+            };
+            let user_agent = var(HEALTHCHECKS_USERAGENT).ok().filter(|ua| !ua.is_empty());
+            (user_agent, timeout)
+        };
+        let mut client = PingClient::from_url(url)?;
+        if let Some(ref user_agent) = user_agent {
+            client = client.set_user_agent(user_agent);
+        }
+        if let Some(timeout) = timeout {
+            client = client.set_timeout(Duration::from_secs(timeout));
+        }
+        Ok(client)
+    }
+}
+
+/// Pick the profile named `name`, or the one named by `HCCTL_PROFILE`, from
+/// the configuration file.
+pub(crate) fn select(name: Option<&str>) -> Result<Selection> {
+    let name = name
+        .map(str::to_owned)
+        .or_else(|| var(HCCTL_PROFILE).ok().filter(|name| !name.is_empty()));
+    let token_set = var(HEALTHCHECKS_TOKEN).is_ok_and(|token| !token.is_empty());
+    let config = match config_path() {
+        Some(path) => ConfigFile::load(&path)?,
+        None => ConfigFile::default(),
+    };
+    config.select(name.as_deref(), token_set)
+}
+
+/// Every profile in the configuration file, for commands that span projects.
+pub(crate) fn all_profiles() -> Result<Vec<Selection>> {
+    let path = config_path().ok_or_else(|| {
+        eyre!("Could not find the home directory, set {HCCTL_CONFIG} to the configuration file")
+    })?;
+    let file = ConfigFile::load(&path)?;
+    if file.profiles.is_empty() {
+        return Err(eyre!(
+            "No profiles in {}, add one with `hcctl config set <name>`",
+            path.display()
+        ));
+    }
+    Ok(file
+        .profiles
+        .into_iter()
+        .map(|(name, profile)| Selection {
+            name: Some(name),
+            profile,
+        })
+        .collect())
+}
+
+/// Run the API key command of a profile through the shell and return what it printed.
+fn run_key_command(profile: &str, command: &str) -> Result<String> {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    // Secret managers can prompt for a passphrase, so only standard output is captured
+    let output = Command::new(shell)
+        .args([flag, command])
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .wrap_err_with(|| format!("Failed to run the API key command of profile '{profile}'"))?;
+    if !output.status.success() {
+        return Err(eyre!(
+            "The API key command of profile '{profile}' failed with {}",
+            output.status
+        ));
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+}
+
+/// Run a `config` subcommand.
+pub(crate) fn run(config: &Config, out: &Output) -> Result<()> {
+    let path = config_path().ok_or_else(|| {
+        eyre!("Could not find the home directory, set {HCCTL_CONFIG} to the configuration file")
+    })?;
+    let mut file = ConfigFile::load(&path)?;
+    match &config.action {
+        ConfigAction::Path => {
+            println!("{}", path.display());
+            return Ok(());
+        }
+        ConfigAction::List => {
+            let records: Vec<ProfileRecord<'_>> = file
+                .profiles
+                .iter()
+                .map(|(name, profile)| ProfileRecord::new(name, profile, &file))
+                .collect();
+            return out.print(&records);
+        }
+        ConfigAction::Set(set) => {
+            set_profile(file.profiles.entry(set.name.clone()).or_default(), set)?;
+            if file.default_profile.is_none() {
+                file.default_profile = Some(set.name.clone());
+            }
+            eprintln!("Saved profile '{}' to {}", set.name, path.display());
+        }
+        ConfigAction::Remove { name } => {
+            if file.profiles.remove(name).is_none() {
+                return Err(file.unknown_profile(name));
+            }
+            if file.default_profile.as_ref() == Some(name) {
+                file.default_profile = None;
+            }
+            eprintln!("Removed profile '{name}'");
+        }
+        ConfigAction::Use { name } => {
+            if !file.profiles.contains_key(name) {
+                return Err(file.unknown_profile(name));
+            }
+            eprintln!("Using profile '{name}' by default");
+            file.default_profile = Some(name.clone());
+        }
+    }
+    file.save(&path)
+}
+
+/// Apply the fields given to `config set` to `profile`.
+fn set_profile(profile: &mut Profile, set: &SetProfile) -> Result<()> {
+    for field in &set.unset {
+        match field {
+            ProfileField::ApiKey => profile.api_key = None,
+            ProfileField::ApiKeyCommand => profile.api_key_command = None,
+            ProfileField::ApiUrl => profile.api_url = None,
+            ProfileField::UserAgent => profile.user_agent = None,
+            ProfileField::Timeout => profile.timeout = None,
+            ProfileField::DefaultOutput => profile.output = None,
+            ProfileField::DefaultColumns => profile.columns = None,
+        }
+    }
+    if let Some(ref api_key) = set.api_key {
+        // Reading the key from standard input keeps it out of the shell history
+        let api_key = if api_key == "-" {
+            let mut line = String::new();
+            io::stdin().lock().read_line(&mut line)?;
+            line.trim().to_owned()
+        } else {
+            api_key.clone()
+        };
+        profile.api_key = Some(api_key);
+        profile.api_key_command = None;
+    }
+    if let Some(ref command) = set.api_key_command {
+        profile.api_key_command = Some(command.clone());
+        profile.api_key = None;
+    }
+    if set.api_url.is_some() {
+        profile.api_url.clone_from(&set.api_url);
+    }
+    if set.user_agent.is_some() {
+        profile.user_agent.clone_from(&set.user_agent);
+    }
+    if set.timeout.is_some() {
+        profile.timeout = set.timeout;
+    }
+    if set.default_output.is_some() {
+        profile.output = set.default_output;
+    }
+    if set.default_columns.is_some() {
+        profile.columns.clone_from(&set.default_columns);
+    }
+    Ok(())
+}
+
+/// A profile listed by `config list`, without its API key.
+#[derive(Serialize)]
+struct ProfileRecord<'a> {
+    name: &'a str,
+    default: bool,
+    api_url: Option<&'a str>,
+    api_key: Option<&'static str>,
+    api_key_command: Option<&'a str>,
+    user_agent: Option<&'a str>,
+    timeout: Option<u64>,
+    output: Option<OutputFormat>,
+    columns: Option<String>,
+}
+
+impl<'a> ProfileRecord<'a> {
+    fn new(name: &'a str, profile: &'a Profile, file: &ConfigFile) -> Self {
+        ProfileRecord {
+            name,
+            default: file.default_profile.as_deref() == Some(name),
+            api_url: profile.api_url.as_deref(),
+            api_key: profile.api_key.as_ref().map(|_| "stored"),
+            api_key_command: profile.api_key_command.as_deref(),
+            user_agent: profile.user_agent.as_deref(),
+            timeout: profile.timeout,
+            output: profile.output,
+            columns: profile.columns.as_ref().map(|columns| columns.join(",")),
+        }
+    }
+}
+
+impl Record for ProfileRecord<'_> {
+    fn columns() -> &'static [(&'static str, &'static str)] {
+        &[
+            ("default", ""),
+            ("name", "Name"),
+            ("api_url", "API URL"),
+            ("api_key", "API key"),
+            ("api_key_command", "API key command"),
+            ("output", "Output"),
+        ]
+    }
+
+    fn table_cell(&self, key: &str, value: &Value) -> Result<String> {
+        Ok(match (key, value) {
+            ("default", _) => if self.default { "*" } else { "" }.to_owned(),
+            (_, Value::Null) => "-".to_owned(),
+            (_, value) => crate::output::cell(value),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+default_profile = "prod"
+
+[profiles.prod]
+api_key_command = "pass show healthchecks/prod"
+output = "json"
+
+[profiles.selfhosted]
+api_key = "secret"
+api_url = "https://hc.example.com/api/v3"
+columns = ["id", "name"]
+"#;
+
+    #[test]
+    fn selects_profiles() {
+        let file: ConfigFile = toml::from_str(CONFIG).unwrap();
+        let selected = file.select(None, false).unwrap();
+        assert_eq!(selected.name.as_deref(), Some("prod"));
+        assert_eq!(selected.profile.output, Some(OutputFormat::Json));
+        // The environment wins over the default profile, but not over a selected one
+        assert_eq!(file.select(None, true).unwrap().name, None);
+        let selected = file.select(Some("selfhosted"), true).unwrap();
+        assert_eq!(
+            selected.profile.api_url.as_deref(),
+            Some("https://hc.example.com/api/v3")
+        );
+        assert_eq!(
+            file.select(Some("staging"), false).unwrap_err().to_string(),
+            "No profile named 'staging', available profiles: prod, selfhosted"
+        );
+        assert!(toml::from_str::<ConfigFile>("[profiles.prod]\napi_token = \"x\"").is_err());
+    }
+
+    #[test]
+    fn creates_clients_from_profiles() {
+        let file: ConfigFile = toml::from_str(CONFIG).unwrap();
+        assert!(file
+            .select(Some("selfhosted"), false)
+            .unwrap()
+            .client()
+            .is_ok());
+        let mut selection = file.select(Some("prod"), false).unwrap();
+        selection.profile.api_key_command = Some("exit 1".to_owned());
+        assert!(selection.client().is_err());
+        selection.profile.api_key_command = None;
+        assert_eq!(
+            selection.client().err().map(|e| e.to_string()).as_deref(),
+            Some("Profile 'prod' has no api_key or api_key_command")
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use crate::cli::parse_duration;
+use crate::status::STATUSES;
+use clap::{Args, ValueEnum};
+use healthchecks::model::Check;
+use jiff::Timestamp;
+use regex::{Regex, RegexBuilder};
+use std::cmp::Ordering;
+use std::time::Duration;
+
+/// Filters and ordering shared by the `list`, `search` and `pings` subcommands
+#[derive(Args, Clone, Debug, Default)]
+pub(crate) struct CheckFilter {
+    /// Case insensitive regular expression matched against the name, slug and description of checks
+    #[arg(long, value_parser = parse_regex)]
+    pub(crate) regex: Option<Regex>,
+    /// Only include checks with any of these tags, can be repeated or comma separated
+    #[arg(long, value_delimiter = ',')]
+    pub(crate) tag: Vec<String>,
+    /// Leave out checks with any of these tags, can be repeated or comma separated
+    #[arg(long, value_delimiter = ',')]
+    pub(crate) exclude_tag: Vec<String>,
+    /// Only include checks with any of these statuses, comma separated
+    #[arg(long, value_delimiter = ',', value_parser = STATUSES)]
+    pub(crate) status: Vec<String>,
+    /// Only include checks that last pinged longer ago than this, or never did, in seconds or with a m, h, d or w suffix
+    #[arg(long, value_parser = parse_duration)]
+    pub(crate) older_than: Option<Duration>,
+    /// Only include checks that pinged within this long, in seconds or with a m, h, d or w suffix
+    #[arg(long, value_parser = parse_duration)]
+    pub(crate) newer_than: Option<Duration>,
+    /// Order of the checks, instead of the order of the API
+    #[arg(long, value_enum)]
+    pub(crate) sort: Option<SortKey>,
+}
+
+/// Field checks are ordered by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum SortKey {
+    /// Alphabetically, ignoring case
+    Name,
+    /// Longest since the last ping first, starting with checks that never pinged
+    LastPing,
+    /// Most severe status first
+    Status,
+}
+
+impl CheckFilter {
+    /// Whether no filter or ordering is set.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.regex.is_none()
+            && self.tag.is_empty()
+            && self.exclude_tag.is_empty()
+            && self.status.is_empty()
+            && self.older_than.is_none()
+            && self.newer_than.is_none()
+            && self.sort.is_none()
+    }
+
+    /// Whether `check` passes every filter, with ping ages relative to `now`.
+    pub(crate) fn matches(&self, check: &Check, now: Timestamp) -> bool {
+        let has_tag = |tags: &[String]| {
+            check
+                .tags
+                .split_whitespace()
+                .any(|t| tags.iter().any(|tag| tag == t))
+        };
+        // Pings from the future count as just received
+        let age = last_ping(check)
+            .map(|last_ping| Duration::try_from(now.duration_since(last_ping)).unwrap_or_default());
+        self.regex.as_ref().is_none_or(|regex| {
+            regex.is_match(&check.name)
+                || regex.is_match(&check.desc)
+                || check
+                    .slug
+                    .as_deref()
+                    .is_some_and(|slug| regex.is_match(slug))
+        }) && (self.tag.is_empty() || has_tag(&self.tag))
+            && !has_tag(&self.exclude_tag)
+            && (self.status.is_empty() || self.status.contains(&check.status))
+            && self
+                .older_than
+                .is_none_or(|older_than| age.is_none_or(|age| age > older_than))
+            && self
+                .newer_than
+                .is_none_or(|newer_than| age.is_some_and(|age| age <= newer_than))
+    }
+
+    /// Keep the items whose check passes every filter, ordered by the sort key.
+    pub(crate) fn apply<T>(&self, items: &mut Vec<T>, check: impl Fn(&T) -> &Check) {
+        let now = Timestamp::now();
+        items.retain(|item| self.matches(check(item), now));
+        if let Some(sort) = self.sort {
+            items.sort_by(|a, b| sort.compare(check(a), check(b)));
+        }
+    }
+}
+
+impl SortKey {
+    fn compare(self, a: &Check, b: &Check) -> Ordering {
+        match self {
+            SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortKey::LastPing => last_ping(a).cmp(&last_ping(b)),
+            SortKey::Status => severity(&a.status).cmp(&severity(&b.status)),
+        }
+    }
+}
+
+fn parse_regex(value: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(value).case_insensitive(true).build()
+}
+
+fn last_ping(check: &Check) -> Option<Timestamp> {
+    check
+        .last_ping
+        .as_deref()
+        .and_then(|last_ping| last_ping.parse().ok())
+}
+
+fn severity(status: &str) -> usize {
+    STATUSES
+        .iter()
+        .position(|s| *s == status)
+        .unwrap_or(STATUSES.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::check;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Opts {
+        #[command(flatten)]
+        filter: CheckFilter,
+    }
+
+    fn filter(args: &[&str]) -> CheckFilter {
+        Opts::try_parse_from(std::iter::once("filter").chain(args.iter().copied()))
+            .unwrap()
+            .filter
+    }
+
+    fn names(filter: &CheckFilter) -> Vec<String> {
+        let mut checks = vec![
+            check("Backup", "prod db", "up", Some("2024-01-01T11:00:00+00:00")),
+            check("vacuum", "db", "down", Some("2024-01-01T06:00:00+00:00")),
+            check("Reports", "prod", "grace", None),
+        ];
+        let now: Timestamp = "2024-01-01T12:00:00+00:00".parse().unwrap();
+        checks.retain(|check| filter.matches(check, now));
+        if let Some(sort) = filter.sort {
+            checks.sort_by(|a, b| sort.compare(a, b));
+        }
+        checks.into_iter().map(|check| check.name).collect()
+    }
+
+    #[test]
+    fn filters_checks() {
+        assert!(filter(&[]).is_empty());
+        assert!(!filter(&["--sort", "name"]).is_empty());
+        assert_eq!(names(&filter(&[])), ["Backup", "vacuum", "Reports"]);
+        assert_eq!(
+            names(&filter(&["--regex", "^rep|vac"])),
+            ["vacuum", "Reports"]
+        );
+        assert_eq!(names(&filter(&["--regex", "backup job"])), ["Backup"]);
+        assert_eq!(
+            names(&filter(&["--tag", "db", "--exclude-tag", "prod"])),
+            ["vacuum"]
+        );
+        assert_eq!(
+            names(&filter(&["--status", "down,grace"])),
+            ["vacuum", "Reports"]
+        );
+        assert_eq!(
+            names(&filter(&["--older-than", "2h"])),
+            ["vacuum", "Reports"]
+        );
+        assert_eq!(names(&filter(&["--newer-than", "2h"])), ["Backup"]);
+        assert!(Opts::try_parse_from(["filter", "--status", "late"]).is_err());
+        assert!(Opts::try_parse_from(["filter", "--regex", "("]).is_err());
+    }
+
+    #[test]
+    fn sorts_checks() {
+        assert_eq!(
+            names(&filter(&["--sort", "name"])),
+            ["Backup", "Reports", "vacuum"]
+        );
+        assert_eq!(
+            names(&filter(&["--sort", "last-ping"])),
+            ["Reports", "vacuum", "Backup"]
+        );
+        assert_eq!(
+            names(&filter(&["--sort", "status"])),
+            ["vacuum", "Reports", "Backup"]
+        );
+    }
+}
@@ -1,33 +1,109 @@
+mod cache;
 mod cli;
 mod cmds;
-
-use std::env::var;
+mod completions;
+mod config;
+mod filter;
+mod metrics;
+mod nagios;
+mod output;
+mod project;
+mod status;
+#[cfg(test)]
+mod test_utils;
+mod watch;
 
 use clap::Parser;
-use cli::{Opts, Settings, SubCommand};
-use color_eyre::Result;
-
-const HEALTHCHECKS_TOKEN: &str = "HEALTHCHECKS_TOKEN";
-const HEALTHCHECKS_USERAGENT: &str = "HEALTHCHECKS_USERAGENT";
+use cli::{Opts, SubCommand};
+use color_eyre::{eyre::eyre, Result};
+use config::Profile;
+use std::net::TcpListener;
 
 fn main() -> Result<()> {
+    completions::complete();
     color_eyre::install()?;
     let opts = Opts::parse();
 
-    let ua = var(HEALTHCHECKS_USERAGENT).ok();
-    let settings = Settings {
-        token: var(HEALTHCHECKS_TOKEN)?,
-        ua,
-    };
     match opts.subcommand {
-        SubCommand::List(_) => {
-            cmds::list(settings)?;
+        SubCommand::Completions(ref c) => return completions::print_registration(c.shell),
+        SubCommand::Config(ref c) => return config::run(c, &opts.output(&Profile::default())),
+        SubCommand::Man(ref m) => return completions::write_man_pages(&m.dir),
+        _ => {}
+    }
+    if opts.profile.is_some() && opts.subcommand.spans_profiles() {
+        return Err(eyre!("--profile cannot be used with --all-profiles"));
+    }
+    let selection = config::select(opts.profile.as_deref());
+    // Plugins report every failure through their exit code, including configuration errors
+    if let SubCommand::CheckStatus(ref c) = opts.subcommand {
+        let client = selection.and_then(|selection| selection.client());
+        std::process::exit(nagios::run(client, &c.checks, &c.tag));
+    }
+    let selection = selection?;
+    cache::use_profile(selection.name.as_deref());
+    let out = opts.output(&selection.profile);
+    match opts.subcommand {
+        SubCommand::List(ref l) if l.all_profiles => {
+            return cmds::list_all_profiles(&l.filter, &out);
+        }
+        SubCommand::Search(ref s) if s.all_profiles => {
+            return cmds::search_all_profiles(&s.search_term, &s.filter, &out);
+        }
+        _ => {}
+    }
+    let client = selection.client()?;
+    match opts.subcommand {
+        SubCommand::Apply(a) => {
+            cmds::apply(&client, &a.file, a.prune, a.dry_run, &out)?;
+        }
+        SubCommand::Channels(c) => {
+            cmds::channels(&client, c.action, &out)?;
+        }
+        SubCommand::CheckStatus(_)
+        | SubCommand::Completions(_)
+        | SubCommand::Config(_)
+        | SubCommand::Man(_) => {
+            unreachable!("handled before creating the client")
+        }
+        SubCommand::Create(c) => {
+            cmds::create(&client, c, &out)?;
+        }
+        SubCommand::Delete(d) => {
+            cmds::delete(&client, &d, &out)?;
+        }
+        SubCommand::Export(e) => {
+            cmds::export(&client, e.file.as_deref(), e.format, &out)?;
+        }
+        SubCommand::Import(i) => {
+            cmds::import(&client, &i.file, i.format, &out)?;
+        }
+        SubCommand::List(l) => {
+            cmds::list(&client, &l.filter, &out)?;
+        }
+        SubCommand::Pause(p) => {
+            cmds::pause(&client, &p, &out)?;
+        }
+        SubCommand::Ping(p) => {
+            cmds::ping(&client, &selection, &p)?;
         }
         SubCommand::Pings(p) => {
-            cmds::pings(settings, &p.check_id)?;
+            cmds::pings(&client, &p, &out)?;
         }
         SubCommand::Search(s) => {
-            cmds::search(settings, &s.search_term)?;
+            cmds::search(&client, &s.search_term, &s.filter, &out)?;
+        }
+        SubCommand::ServeMetrics(s) => {
+            let listener = TcpListener::bind(s.listen)?;
+            metrics::serve(client, &listener, s.interval, !s.no_durations)?;
+        }
+        SubCommand::Stats(s) => {
+            cmds::stats(&client, &s.check, s.window, &out)?;
+        }
+        SubCommand::Update(u) => {
+            cmds::update(&client, u, &out)?;
+        }
+        SubCommand::Watch(w) => {
+            watch::run(&client, w.interval, w.tag)?;
         }
     }
 
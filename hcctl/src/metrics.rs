@@ -0,0 +1,385 @@
+use color_eyre::Result;
+use healthchecks::errors::HealthchecksApiError;
+use healthchecks::manage::ManageClient;
+use healthchecks::model::Check;
+use jiff::Timestamp;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::status::STATUSES;
+
+/// Content type of the Prometheus text exposition format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Latest state of the project, refreshed in the background so that scrapes
+/// never wait on the API.
+#[derive(Default)]
+pub(crate) struct Snapshot {
+    checks: Vec<Check>,
+    /// Duration of the latest timed run of each check, by UUID.
+    durations: HashMap<String, f64>,
+    refreshed_at: Option<Timestamp>,
+    refresh_errors: u64,
+}
+
+impl Snapshot {
+    /// Replace the checks with freshly fetched ones, or count the failed refresh
+    /// and keep serving the previous checks.
+    fn update(&mut self, fetched: Result<Fetched, HealthchecksApiError>) {
+        match fetched {
+            Ok((checks, durations)) => {
+                self.checks = checks;
+                self.durations = durations;
+                self.refreshed_at = Some(Timestamp::now());
+            }
+            Err(error) => {
+                eprintln!("Failed to refresh checks: {error}");
+                self.refresh_errors += 1;
+            }
+        }
+    }
+
+    /// Render the metrics in the Prometheus text format, with ages relative to `now`.
+    pub(crate) fn render(&self, now: Timestamp) -> String {
+        let mut out = String::new();
+        let labels: Vec<String> = self.checks.iter().map(labels).collect();
+        let checks = || self.checks.iter().zip(&labels);
+
+        metric(
+            &mut out,
+            "healthchecks_check_status",
+            "gauge",
+            "Status of the check, 1 for its current status and 0 for the others.",
+        );
+        for (check, labels) in checks() {
+            for status in STATUSES {
+                let value = u8::from(check.status == status);
+                let _ = writeln!(
+                    out,
+                    "healthchecks_check_status{{{labels},status=\"{status}\"}} {value}"
+                );
+            }
+        }
+
+        metric(
+            &mut out,
+            "healthchecks_check_last_ping_age_seconds",
+            "gauge",
+            "Seconds since the check last received a ping.",
+        );
+        for (check, labels) in checks() {
+            if let Some(last_ping) = timestamp(check.last_ping.as_deref()) {
+                let age = now.duration_since(last_ping).as_secs();
+                let _ = writeln!(
+                    out,
+                    "healthchecks_check_last_ping_age_seconds{{{labels}}} {age}"
+                );
+            }
+        }
+
+        metric(
+            &mut out,
+            "healthchecks_check_pings_total",
+            "counter",
+            "Number of pings received by the check.",
+        );
+        for (check, labels) in checks() {
+            let _ = writeln!(
+                out,
+                "healthchecks_check_pings_total{{{labels}}} {}",
+                check.n_pings
+            );
+        }
+
+        metric(
+            &mut out,
+            "healthchecks_check_timeout_seconds",
+            "gauge",
+            "Expected time between pings of checks without a schedule.",
+        );
+        for (check, labels) in checks() {
+            if let (Some(timeout), None) = (check.timeout, &check.schedule) {
+                let _ = writeln!(
+                    out,
+                    "healthchecks_check_timeout_seconds{{{labels}}} {timeout}"
+                );
+            }
+        }
+
+        metric(
+            &mut out,
+            "healthchecks_check_grace_seconds",
+            "gauge",
+            "Time to wait after a missed ping before alerting.",
+        );
+        for (check, labels) in checks() {
+            let _ = writeln!(
+                out,
+                "healthchecks_check_grace_seconds{{{labels}}} {}",
+                check.grace
+            );
+        }
+
+        metric(
+            &mut out,
+            "healthchecks_check_last_duration_seconds",
+            "gauge",
+            "Duration of the latest run of the check, between its start and finish pings.",
+        );
+        for (check, labels) in checks() {
+            let duration = check.id().and_then(|id| self.durations.get(&id).copied());
+            if let Some(duration) = duration {
+                let _ = writeln!(
+                    out,
+                    "healthchecks_check_last_duration_seconds{{{labels}}} {duration}"
+                );
+            }
+        }
+
+        metric(
+            &mut out,
+            "healthchecks_last_refresh_timestamp_seconds",
+            "gauge",
+            "Unix time of the latest successful refresh of the checks.",
+        );
+        if let Some(refreshed_at) = self.refreshed_at {
+            let _ = writeln!(
+                out,
+                "healthchecks_last_refresh_timestamp_seconds {}",
+                refreshed_at.as_second()
+            );
+        }
+
+        metric(
+            &mut out,
+            "healthchecks_refresh_errors_total",
+            "counter",
+            "Number of failed refreshes of the checks.",
+        );
+        let _ = writeln!(
+            out,
+            "healthchecks_refresh_errors_total {}",
+            self.refresh_errors
+        );
+        out
+    }
+}
+
+/// Checks and the durations of their latest runs.
+type Fetched = (Vec<Check>, HashMap<String, f64>);
+
+/// Fetch the checks, and the duration of their latest run if `durations` is set.
+fn fetch(client: &ManageClient, durations: bool) -> Result<Fetched, HealthchecksApiError> {
+    let checks = client.get_checks()?;
+    let durations = if durations {
+        checks
+            .iter()
+            .filter_map(|check| {
+                let id = check.id()?;
+                let pings = client.list_logged_pings(&id).ok()?;
+                // Pings are listed newest first
+                let duration = pings.iter().find_map(|ping| ping.duration)?;
+                Some((id, duration))
+            })
+            .collect()
+    } else {
+        HashMap::new()
+    };
+    Ok((checks, durations))
+}
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} {kind}");
+}
+
+/// Labels of `check`. The UUID, or the unique key with read-only API keys,
+/// keeps the label sets of checks with the same name or slug apart.
+fn labels(check: &Check) -> String {
+    let uuid = check.id().or_else(|| check.unique_key.clone());
+    format!(
+        "uuid=\"{}\",name=\"{}\",slug=\"{}\",tags=\"{}\"",
+        escape(uuid.as_deref().unwrap_or_default()),
+        escape(&check.name),
+        escape(check.slug.as_deref().unwrap_or_default()),
+        escape(&check.tags)
+    )
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn timestamp(value: Option<&str>) -> Option<Timestamp> {
+    value.and_then(|value| value.parse().ok())
+}
+
+/// Refresh the checks every `interval` in the background and serve them on
+/// `listener` until the process is stopped.
+pub(crate) fn serve(
+    client: ManageClient,
+    listener: &TcpListener,
+    interval: Duration,
+    durations: bool,
+) -> Result<()> {
+    let snapshot = Arc::new(Mutex::new(Snapshot::default()));
+    snapshot.lock().unwrap().update(fetch(&client, durations));
+
+    let background = Arc::clone(&snapshot);
+    thread::spawn(move || loop {
+        thread::sleep(interval);
+        let fetched = fetch(&client, durations);
+        background.lock().unwrap().update(fetched);
+    });
+
+    eprintln!(
+        "Serving metrics on http://{}/metrics",
+        listener.local_addr()?
+    );
+    // Every connection gets its own thread so that a slow client cannot hold
+    // up the scrapes of the others
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let snapshot = Arc::clone(&snapshot);
+                thread::spawn(move || {
+                    if let Err(error) = handle(stream, &snapshot) {
+                        eprintln!("Failed to answer request: {error}");
+                    }
+                });
+            }
+            Err(error) => eprintln!("Failed to accept connection: {error}"),
+        }
+    }
+    Ok(())
+}
+
+/// Answer a single HTTP request, serving the metrics on `/metrics`.
+fn handle(mut stream: TcpStream, snapshot: &Mutex<Snapshot>) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // The headers are not needed, but have to be read before responding
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let body = snapshot.lock().unwrap().render(Timestamp::now());
+            ("200 OK", CONTENT_TYPE, body)
+        }
+        (Some("GET"), Some("/")) => (
+            "200 OK",
+            "text/plain; charset=utf-8",
+            "healthchecks exporter, metrics are served on /metrics\n".to_owned(),
+        ),
+        (Some("GET"), _) => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Not found\n".to_owned(),
+        ),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "Method not allowed\n".to_owned(),
+        ),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::check;
+    use std::io::Read;
+
+    fn snapshot() -> Snapshot {
+        let mut check = check(
+            "nightly \"backup\"",
+            "prod db",
+            "grace",
+            Some("2024-01-01T11:00:00+00:00"),
+        );
+        check.slug = Some("nightly-backup".to_owned());
+        check.n_pings = 42;
+        check.ping_url =
+            Some("https://hc-ping.com/5b8e4f5e-fc5d-4a2b-8c6e-6b9a8a1c2d3e".to_owned());
+        Snapshot {
+            durations: HashMap::from([(check.id().unwrap(), 12.5)]),
+            checks: vec![check],
+            refreshed_at: Some("2024-01-01T11:59:00+00:00".parse().unwrap()),
+            refresh_errors: 1,
+        }
+    }
+
+    #[test]
+    fn renders_check_metrics() {
+        let now: Timestamp = "2024-01-01T12:00:00+00:00".parse().unwrap();
+        let metrics = snapshot().render(now);
+        let labels = r#"uuid="5b8e4f5e-fc5d-4a2b-8c6e-6b9a8a1c2d3e",name="nightly \"backup\"",slug="nightly-backup",tags="prod db""#;
+        for line in [
+            format!("healthchecks_check_status{{{labels},status=\"grace\"}} 1"),
+            format!("healthchecks_check_status{{{labels},status=\"up\"}} 0"),
+            format!("healthchecks_check_last_ping_age_seconds{{{labels}}} 3600"),
+            format!("healthchecks_check_pings_total{{{labels}}} 42"),
+            format!("healthchecks_check_timeout_seconds{{{labels}}} 86400"),
+            format!("healthchecks_check_grace_seconds{{{labels}}} 3600"),
+            format!("healthchecks_check_last_duration_seconds{{{labels}}} 12.5"),
+            "healthchecks_last_refresh_timestamp_seconds 1704110340".to_owned(),
+            "healthchecks_refresh_errors_total 1".to_owned(),
+            "# TYPE healthchecks_check_pings_total counter".to_owned(),
+        ] {
+            assert!(
+                metrics.lines().any(|l| l == line),
+                "missing {line} in\n{metrics}"
+            );
+        }
+    }
+
+    #[test]
+    fn labels_read_only_checks_by_unique_key() {
+        let mut check = check("backup", "", "new", None);
+        check.unique_key = Some("a6c7b0a8a66bed0df66abfdab3c77736861703ee".to_owned());
+        assert_eq!(
+            labels(&check),
+            r#"uuid="a6c7b0a8a66bed0df66abfdab3c77736861703ee",name="backup",slug="backup",tags="""#
+        );
+    }
+
+    #[test]
+    fn serves_metrics_over_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let snapshot = Mutex::new(snapshot());
+        let request = |path: &str| {
+            let mut client = TcpStream::connect(addr).unwrap();
+            write!(client, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+            handle(listener.accept().unwrap().0, &snapshot).unwrap();
+            let mut response = String::new();
+            client.read_to_string(&mut response).unwrap();
+            response
+        };
+        let response = request("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(CONTENT_TYPE));
+        assert!(response.contains("healthchecks_check_pings_total"));
+        assert!(request("/other").starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
@@ -0,0 +1,202 @@
+use color_eyre::Result;
+use healthchecks::manage::ManageClient;
+use healthchecks::model::Check;
+use jiff::Timestamp;
+use std::fmt::Write;
+
+use crate::status::STATUSES;
+
+/// Service state reported by a monitoring plugin, with its exit code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum PluginState {
+    Ok = 0,
+    Warning = 1,
+    Critical = 2,
+    Unknown = 3,
+}
+
+impl PluginState {
+    fn of(status: &str) -> PluginState {
+        match status {
+            "down" => PluginState::Critical,
+            "grace" => PluginState::Warning,
+            _ => PluginState::Ok,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PluginState::Ok => "OK",
+            PluginState::Warning => "WARNING",
+            PluginState::Critical => "CRITICAL",
+            PluginState::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+/// Query the checks and print a plugin summary line, returning the exit code.
+/// Errors, including failing to create the client, are reported as
+/// [`PluginState::Unknown`] rather than returned, as the exit code is what
+/// monitoring systems act on.
+pub(crate) fn run(client: Result<ManageClient>, terms: &[String], tags: &[String]) -> i32 {
+    let checks = client
+        .map_err(|e| e.to_string())
+        .and_then(|client| client.get_checks().map_err(|e| e.to_string()))
+        .and_then(|checks| select(checks, terms, tags));
+    let (state, line) = match checks {
+        Ok(checks) => summarize(&checks, Timestamp::now()),
+        Err(error) => (PluginState::Unknown, error),
+    };
+    println!("HEALTHCHECKS {} - {line}", state.label());
+    state as i32
+}
+
+/// Pick the checks matching any of `terms` by UUID, unique key, slug or name,
+/// along with the ones that have any of `tags`. Every check is picked when
+/// neither are given.
+pub(crate) fn select(
+    checks: Vec<Check>,
+    terms: &[String],
+    tags: &[String],
+) -> Result<Vec<Check>, String> {
+    if terms.is_empty() && tags.is_empty() {
+        return Ok(checks);
+    }
+    if let Some(term) = terms
+        .iter()
+        .find(|term| !checks.iter().any(|check| matches(check, term)))
+    {
+        return Err(format!("no check matching '{term}'"));
+    }
+    let selected: Vec<Check> = checks
+        .into_iter()
+        .filter(|check| {
+            terms.iter().any(|term| matches(check, term))
+                || check
+                    .tags
+                    .split_whitespace()
+                    .any(|tag| tags.iter().any(|t| t == tag))
+        })
+        .collect();
+    if selected.is_empty() {
+        return Err(format!("no checks tagged {}", tags.join(" or ")));
+    }
+    Ok(selected)
+}
+
+fn matches(check: &Check, term: &str) -> bool {
+    check.id().as_deref() == Some(term)
+        || check.unique_key.as_deref() == Some(term)
+        || check.slug.as_deref() == Some(term)
+        || check.name == term
+}
+
+/// Overall state of `checks` and the plugin output describing them, with
+/// perfdata for the number of checks in each status and the time since each
+/// check last pinged.
+pub(crate) fn summarize(checks: &[Check], now: Timestamp) -> (PluginState, String) {
+    let state = checks
+        .iter()
+        .map(|check| PluginState::of(&check.status))
+        .max()
+        .unwrap_or(PluginState::Ok);
+
+    let mut summary = Vec::new();
+    let mut perfdata = Vec::new();
+    for status in STATUSES {
+        let names: Vec<&str> = checks
+            .iter()
+            .filter(|check| check.status == status)
+            .map(|check| check.name.as_str())
+            .collect();
+        perfdata.push(format!("{status}={};;;0", names.len()));
+        match (names.len(), PluginState::of(status)) {
+            (0, _) => {}
+            (count, PluginState::Ok) => summary.push(format!("{count} {status}")),
+            (count, _) => summary.push(format!("{count} {status} ({})", names.join(", "))),
+        }
+    }
+    for check in checks {
+        let Some(last_ping) = check
+            .last_ping
+            .as_deref()
+            .and_then(|ts| ts.parse::<Timestamp>().ok())
+        else {
+            continue;
+        };
+        let mut data = format!(
+            "'{}'={}s;",
+            check.name.replace('\'', "''").replace('=', "_"),
+            now.duration_since(last_ping).as_secs()
+        );
+        // Simple checks go late after their timeout and down after their grace time
+        if let (Some(timeout), None) = (check.timeout, &check.schedule) {
+            let _ = write!(data, "{timeout};{}", timeout + check.grace);
+        } else {
+            data.push(';');
+        }
+        data.push_str(";0");
+        perfdata.push(data);
+    }
+
+    let summary = if summary.is_empty() {
+        "no checks".to_owned()
+    } else {
+        summary.join(", ")
+    };
+    (state, format!("{summary} | {}", perfdata.join(" ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::check;
+
+    fn now() -> Timestamp {
+        "2024-01-01T12:00:00+00:00".parse().unwrap()
+    }
+
+    #[test]
+    fn maps_grace_to_warning_and_down_to_critical() {
+        let up = check("Backup", "prod", "up", Some("2024-01-01T11:00:00+00:00"));
+        let grace = check("Vacuum", "db", "grace", None);
+        let down = check("Reports", "prod", "down", None);
+        assert_eq!(
+            summarize(std::slice::from_ref(&up), now()).0,
+            PluginState::Ok
+        );
+        assert_eq!(
+            summarize(&[up.clone(), grace.clone()], now()).0,
+            PluginState::Warning
+        );
+        let (state, output) = summarize(&[up, grace, down], now());
+        assert_eq!(state, PluginState::Critical);
+        assert_eq!(
+            output,
+            "1 down (Reports), 1 grace (Vacuum), 1 up | down=1;;;0 grace=1;;;0 started=0;;;0 \
+             up=1;;;0 new=0;;;0 paused=0;;;0 'Backup'=3600s;86400;90000;0"
+        );
+    }
+
+    #[test]
+    fn selects_checks_by_slug_name_and_tag() {
+        let checks = vec![
+            check("Backup", "prod", "up", None),
+            check("Nightly vacuum", "db", "up", None),
+            check("Reports", "prod", "up", None),
+        ];
+        let names = |selected: Vec<Check>| -> Vec<String> {
+            selected.into_iter().map(|check| check.name).collect()
+        };
+        let selected = select(checks.clone(), &["nightly-vacuum".to_owned()], &[]).unwrap();
+        assert_eq!(names(selected), ["Nightly vacuum"]);
+        let selected = select(checks.clone(), &["Backup".to_owned()], &["db".to_owned()]).unwrap();
+        assert_eq!(names(selected), ["Backup", "Nightly vacuum"]);
+        assert_eq!(select(checks.clone(), &[], &[]).unwrap().len(), 3);
+        assert_eq!(
+            select(checks.clone(), &["missing".to_owned()], &[]).unwrap_err(),
+            "no check matching 'missing'"
+        );
+        assert!(select(checks, &[], &["staging".to_owned()]).is_err());
+    }
+}
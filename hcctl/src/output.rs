@@ -0,0 +1,306 @@
+use clap::ValueEnum;
+use color_eyre::{eyre::eyre, Result};
+use comfy_table::modifiers::UTF8_ROUND_CORNERS;
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{ContentArrangement, Table};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Format used to print the results of a command.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    ValueEnum,
+    serde_derive::Deserialize,
+    serde_derive::Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum OutputFormat {
+    /// Human readable table
+    #[default]
+    Table,
+    /// JSON array
+    Json,
+    /// One JSON object per line
+    Jsonl,
+    /// Comma separated values
+    Csv,
+    /// YAML sequence
+    Yaml,
+    /// Tab separated values
+    Tsv,
+}
+
+/// A value that can be printed by [`Output`].
+pub(crate) trait Record: Serialize {
+    /// Fields shown in tables when no columns are selected, with their headers.
+    fn columns() -> &'static [(&'static str, &'static str)];
+
+    /// Headers of fields that tables only show when they are selected.
+    fn headers() -> &'static [(&'static str, &'static str)] {
+        &[]
+    }
+
+    /// Fields of the record, which can include computed fields that are not
+    /// part of its serialized form.
+    fn fields(&self) -> Result<Map<String, Value>> {
+        match serde_json::to_value(self)? {
+            Value::Object(fields) => Ok(fields),
+            value => Err(eyre!("expected an object, found {value}")),
+        }
+    }
+
+    /// Human readable form of the field `key` with the given value for tables.
+    fn table_cell(&self, _key: &str, value: &Value) -> Result<String> {
+        Ok(match value {
+            Value::Null => "-".to_owned(),
+            value => cell(value),
+        })
+    }
+}
+
+/// How the results of a command are printed, shared by every subcommand.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Output {
+    pub(crate) format: OutputFormat,
+    pub(crate) columns: Vec<String>,
+    pub(crate) no_header: bool,
+}
+
+impl Output {
+    pub(crate) fn print<R: Record>(&self, records: &[R]) -> Result<()> {
+        print!("{}", self.render(records)?);
+        Ok(())
+    }
+
+    pub(crate) fn render<R: Record>(&self, records: &[R]) -> Result<String> {
+        let rows = records
+            .iter()
+            .map(|record| Ok((record, record.fields()?)))
+            .collect::<Result<Vec<_>>>()?;
+        let columns = self.select_columns::<R>(rows.iter().map(|(_, fields)| fields))?;
+        let selected = |fields: &Map<String, Value>| -> Map<String, Value> {
+            if self.columns.is_empty() {
+                fields.clone()
+            } else {
+                columns
+                    .iter()
+                    .map(|key| (key.clone(), fields.get(key).cloned().unwrap_or(Value::Null)))
+                    .collect()
+            }
+        };
+
+        Ok(match self.format {
+            OutputFormat::Table => {
+                let mut table = Table::new();
+                table
+                    .load_preset(UTF8_FULL)
+                    .apply_modifier(UTF8_ROUND_CORNERS)
+                    .set_content_arrangement(ContentArrangement::Dynamic);
+                if !self.no_header {
+                    table.set_header(columns.iter().map(|key| header::<R>(key)));
+                }
+                for (record, fields) in &rows {
+                    let row = columns
+                        .iter()
+                        .map(|key| record.table_cell(key, fields.get(key).unwrap_or(&Value::Null)))
+                        .collect::<Result<Vec<_>>>()?;
+                    table.add_row(row);
+                }
+                format!("{table}\n")
+            }
+            OutputFormat::Json => {
+                let rows: Vec<_> = rows.iter().map(|(_, fields)| selected(fields)).collect();
+                serde_json::to_string_pretty(&rows)? + "\n"
+            }
+            OutputFormat::Jsonl => rows
+                .iter()
+                .map(|(_, fields)| Ok(serde_json::to_string(&selected(fields))? + "\n"))
+                .collect::<Result<String>>()?,
+            OutputFormat::Yaml => {
+                let rows: Vec<_> = rows.iter().map(|(_, fields)| selected(fields)).collect();
+                serde_yaml::to_string(&rows)?
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let escape = if self.format == OutputFormat::Csv {
+                    escape_csv
+                } else {
+                    escape_tsv
+                };
+                let separator = if self.format == OutputFormat::Csv {
+                    ","
+                } else {
+                    "\t"
+                };
+                let mut output = String::new();
+                if !self.no_header {
+                    let header: Vec<_> = columns.iter().map(|key| escape(key)).collect();
+                    output.push_str(&header.join(separator));
+                    output.push('\n');
+                }
+                for (_, fields) in &rows {
+                    let row: Vec<_> = columns
+                        .iter()
+                        .map(|key| escape(&fields.get(key).map(cell).unwrap_or_default()))
+                        .collect();
+                    output.push_str(&row.join(separator));
+                    output.push('\n');
+                }
+                output
+            }
+        })
+    }
+
+    /// Columns to print: the selected ones, or the defaults of the record for
+    /// tables and every field present in the rows for CSV and TSV.
+    fn select_columns<'a, R: Record>(
+        &self,
+        rows: impl Iterator<Item = &'a Map<String, Value>>,
+    ) -> Result<Vec<String>> {
+        let mut available: Vec<&String> = Vec::new();
+        for fields in rows {
+            for key in fields.keys() {
+                if !available.contains(&key) {
+                    available.push(key);
+                }
+            }
+        }
+        if let Some(unknown) = self.columns.iter().find(|column| {
+            !available.contains(column)
+                && !R::columns()
+                    .iter()
+                    .chain(R::headers())
+                    .any(|(key, _)| key == *column)
+        }) {
+            let available: Vec<&str> = available.iter().map(|key| key.as_str()).collect();
+            return Err(eyre!(
+                "unknown column '{unknown}', available columns: {}",
+                available.join(", ")
+            ));
+        }
+        Ok(if !self.columns.is_empty() {
+            self.columns.clone()
+        } else if self.format == OutputFormat::Table {
+            R::columns()
+                .iter()
+                .map(|(key, _)| (*key).to_owned())
+                .collect()
+        } else {
+            available.into_iter().cloned().collect()
+        })
+    }
+}
+
+fn header<R: Record>(key: &str) -> String {
+    R::columns()
+        .iter()
+        .chain(R::headers())
+        .find(|(column, _)| *column == key)
+        .map_or_else(|| key.to_owned(), |(_, header)| (*header).to_owned())
+}
+
+/// Plain text form of a value, without quotes around strings.
+pub(crate) fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+fn escape_tsv(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::Serialize;
+
+    #[derive(Serialize)]
+    struct Row {
+        name: &'static str,
+        note: Option<&'static str>,
+    }
+
+    impl Record for Row {
+        fn columns() -> &'static [(&'static str, &'static str)] {
+            &[("name", "Name")]
+        }
+    }
+
+    const ROWS: [Row; 2] = [
+        Row {
+            name: "backup",
+            note: Some("a, \"quoted\" note"),
+        },
+        Row {
+            name: "vacuum",
+            note: None,
+        },
+    ];
+
+    fn render(format: OutputFormat, columns: &[&str], no_header: bool) -> Result<String> {
+        Output {
+            format,
+            columns: columns.iter().map(|c| (*c).to_owned()).collect(),
+            no_header,
+        }
+        .render(&ROWS)
+    }
+
+    #[test]
+    fn renders_csv_and_tsv() {
+        assert_eq!(
+            render(OutputFormat::Csv, &[], false).unwrap(),
+            "name,note\nbackup,\"a, \"\"quoted\"\" note\"\nvacuum,\n"
+        );
+        assert_eq!(
+            render(OutputFormat::Tsv, &["note", "name"], true).unwrap(),
+            "a, \"quoted\" note\tbackup\n\tvacuum\n"
+        );
+    }
+
+    #[test]
+    fn renders_json_lines_with_selected_columns() {
+        assert_eq!(
+            render(OutputFormat::Jsonl, &["name"], false).unwrap(),
+            "{\"name\":\"backup\"}\n{\"name\":\"vacuum\"}\n"
+        );
+        assert_eq!(
+            render(OutputFormat::Yaml, &["name"], false).unwrap(),
+            "- name: backup\n- name: vacuum\n"
+        );
+    }
+
+    #[test]
+    fn table_uses_default_columns_and_headers() {
+        let table = render(OutputFormat::Table, &[], false).unwrap();
+        assert!(table.contains("Name"));
+        assert!(!table.contains("quoted"));
+        let table = render(OutputFormat::Table, &["note"], true).unwrap();
+        assert!(table.contains("quoted"));
+        assert_eq!(table.lines().count(), 5);
+    }
+
+    #[test]
+    fn rejects_unknown_columns() {
+        let err = render(OutputFormat::Csv, &["nmae"], false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown column 'nmae', available columns: name, note"
+        );
+    }
+}
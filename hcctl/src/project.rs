@@ -0,0 +1,205 @@
+use clap::ValueEnum;
+use color_eyre::{eyre::eyre, Result};
+use healthchecks::model::{Channel, Check, NewCheck, UniqueField};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Version of the export document, bumped on incompatible changes.
+pub(crate) const EXPORT_VERSION: u32 = 1;
+
+/// Serialization format of an export document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Format {
+    Json,
+    Yaml,
+}
+
+impl Format {
+    /// Pick the format from the extension of `path`, defaulting to JSON.
+    pub(crate) fn from_path(path: Option<&Path>) -> Format {
+        match path.and_then(Path::extension).and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => Format::Yaml,
+            _ => Format::Json,
+        }
+    }
+}
+
+/// Configuration of every check in a project, along with the channels they
+/// reference so that they can be matched up in another project.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ProjectExport {
+    pub(crate) version: u32,
+    pub(crate) exported_at: String,
+    pub(crate) channels: Vec<Channel>,
+    pub(crate) checks: Vec<Check>,
+}
+
+impl ProjectExport {
+    pub(crate) fn new(checks: Vec<Check>, channels: Vec<Channel>, exported_at: String) -> Self {
+        ProjectExport {
+            version: EXPORT_VERSION,
+            exported_at,
+            channels,
+            checks,
+        }
+    }
+
+    pub(crate) fn serialize(&self, format: Format) -> Result<String> {
+        Ok(match format {
+            Format::Json => serde_json::to_string_pretty(self)? + "\n",
+            Format::Yaml => serde_yaml::to_string(self)?,
+        })
+    }
+
+    pub(crate) fn parse(input: &str, format: Format) -> Result<Self> {
+        let export: ProjectExport = match format {
+            Format::Json => serde_json::from_str(input)?,
+            Format::Yaml => serde_yaml::from_str(input)?,
+        };
+        if export.version != EXPORT_VERSION {
+            return Err(eyre!(
+                "unsupported export version {}, expected {EXPORT_VERSION}",
+                export.version
+            ));
+        }
+        Ok(export)
+    }
+
+    /// Map the IDs of the exported channels to the IDs of channels in the `target`
+    /// project with the same name and kind. Returns the map along with the exported
+    /// channels that have no counterpart.
+    pub(crate) fn channel_map<'a>(
+        &'a self,
+        target: &[Channel],
+    ) -> (HashMap<&'a str, String>, Vec<&'a Channel>) {
+        let mut map = HashMap::new();
+        let mut missing = Vec::new();
+        for channel in &self.channels {
+            match target
+                .iter()
+                .find(|c| c.name == channel.name && c.kind == channel.kind)
+            {
+                Some(found) => {
+                    map.insert(channel.id.as_str(), found.id.clone());
+                }
+                None => missing.push(channel),
+            }
+        }
+        (map, missing)
+    }
+}
+
+/// Build the [`NewCheck`] that recreates `check` in another project, using
+/// `channels` to translate its channel IDs. Channels without a translation are
+/// dropped.
+pub(crate) fn new_check(check: &Check, channels: &HashMap<&str, String>) -> NewCheck {
+    let (timeout, schedule, tz) = match check.schedule {
+        Some(ref schedule) => (None, Some(schedule.clone()), check.tz.clone()),
+        None => (check.timeout.map(seconds), None, None),
+    };
+    let mut new = NewCheck::default();
+    new.name = Some(check.name.clone());
+    new.tags = Some(check.tags.clone());
+    new.desc = Some(check.desc.clone());
+    new.timeout = timeout;
+    new.grace = Some(seconds(check.grace));
+    new.schedule = schedule;
+    new.tz = tz;
+    new.manual_resume = Some(check.manual_resume);
+    new.channels = check.channels.as_ref().map(|ids| {
+        ids.split(',')
+            .filter_map(|id| channels.get(id.trim()))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(",")
+    });
+    new.unique = Some(vec![UniqueField::Name.as_str().to_owned()]);
+    new
+}
+
+fn seconds(value: i64) -> i32 {
+    i32::try_from(value).unwrap_or(i32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(id: &str, name: &str, kind: &str) -> Channel {
+        Channel {
+            id: id.to_owned(),
+            name: name.to_owned(),
+            kind: kind.to_owned(),
+        }
+    }
+
+    fn export() -> ProjectExport {
+        let check: Check = serde_json::from_value(serde_json::json!({
+            "name": "backup",
+            "slug": "backup",
+            "tags": "prod",
+            "desc": "Nightly backup",
+            "grace": 3600,
+            "n_pings": 10,
+            "status": "up",
+            "started": false,
+            "manual_resume": false,
+            "schedule": "0 3 * * *",
+            "tz": "Europe/Riga",
+            "channels": "old-email,old-slack",
+        }))
+        .unwrap();
+        ProjectExport::new(
+            vec![check],
+            vec![
+                channel("old-email", "Email", "email"),
+                channel("old-slack", "Ops", "slack"),
+            ],
+            "2024-01-01T00:00:00Z".to_owned(),
+        )
+    }
+
+    #[test]
+    fn round_trips_through_both_formats() {
+        for format in [Format::Json, Format::Yaml] {
+            let serialized = export().serialize(format).unwrap();
+            let parsed = ProjectExport::parse(&serialized, format).unwrap();
+            assert_eq!(parsed.checks[0].name, "backup");
+            assert_eq!(parsed.channels.len(), 2);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let input = export()
+            .serialize(Format::Json)
+            .unwrap()
+            .replace("\"version\": 1", "\"version\": 2");
+        assert!(ProjectExport::parse(&input, Format::Json).is_err());
+    }
+
+    #[test]
+    fn remaps_channels_by_name_and_kind() {
+        let export = export();
+        let target = [
+            channel("new-email", "Email", "email"),
+            channel("new-ops", "Ops", "email"),
+        ];
+        let (map, missing) = export.channel_map(&target);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].name, "Ops");
+        let check = new_check(&export.checks[0], &map);
+        assert_eq!(check.channels.as_deref(), Some("new-email"));
+        assert_eq!(check.schedule.as_deref(), Some("0 3 * * *"));
+        assert_eq!(check.timeout, None);
+        check.validate().unwrap();
+    }
+
+    #[test]
+    fn picks_format_from_extension() {
+        assert_eq!(Format::from_path(Some(Path::new("a.yml"))), Format::Yaml);
+        assert_eq!(Format::from_path(Some(Path::new("a.json"))), Format::Json);
+        assert_eq!(Format::from_path(None), Format::Json);
+    }
+}
@@ -0,0 +1,2 @@
+/// Statuses of checks in order of severity, most severe first.
+pub(crate) const STATUSES: [&str; 6] = ["down", "grace", "started", "up", "new", "paused"];
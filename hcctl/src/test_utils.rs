@@ -0,0 +1,20 @@
+use healthchecks::model::Check;
+
+/// A check with a slug and unique key derived from `name`, and no UUID.
+pub(crate) fn check(name: &str, tags: &str, status: &str, last_ping: Option<&str>) -> Check {
+    serde_json::from_value(serde_json::json!({
+        "name": name,
+        "slug": name.to_lowercase().replace(' ', "-"),
+        "tags": tags,
+        "desc": format!("{name} job"),
+        "grace": 3600,
+        "n_pings": 1,
+        "status": status,
+        "started": false,
+        "manual_resume": false,
+        "last_ping": last_ping,
+        "timeout": 86400,
+        "unique_key": name,
+    }))
+    .unwrap()
+}
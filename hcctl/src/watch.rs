@@ -0,0 +1,539 @@
+use color_eyre::Result;
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use healthchecks::manage::ManageClient;
+use healthchecks::model::{Check, Flip, Ping};
+use jiff::Timestamp;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Stdout, Write};
+use std::time::{Duration, Instant};
+
+/// Group of the checks without any tags, shown after the tagged ones.
+const UNTAGGED: &str = "(untagged)";
+
+/// Checks shown on the dashboard, along with the ones whose status changed in
+/// the last refresh.
+#[derive(Default)]
+pub(crate) struct Dashboard {
+    checks: Vec<Check>,
+    /// Previous status of the checks that changed, by [`key`].
+    changed: HashMap<String, String>,
+    tag: Option<String>,
+}
+
+impl Dashboard {
+    /// Create a dashboard showing every check, or only the ones tagged `tag`.
+    pub(crate) fn new(tag: Option<String>) -> Self {
+        Dashboard {
+            tag,
+            ..Dashboard::default()
+        }
+    }
+
+    /// Replace the checks, remembering which ones changed status since the
+    /// previous refresh. Checks that were added or removed are not transitions.
+    pub(crate) fn refresh(&mut self, checks: Vec<Check>) {
+        let previous: HashMap<String, String> = self
+            .checks
+            .drain(..)
+            .map(|check| (key(&check), check.status))
+            .collect();
+        self.changed = checks
+            .iter()
+            .filter_map(|check| {
+                let status = previous.get(&key(check))?;
+                (*status != check.status).then(|| (key(check), status.clone()))
+            })
+            .collect();
+        self.checks = checks;
+    }
+
+    /// Status the check had before the last refresh, if it changed.
+    pub(crate) fn previous_status(&self, check: &Check) -> Option<&str> {
+        self.changed.get(&key(check)).map(String::as_str)
+    }
+
+    /// Checks grouped by tag and sorted by name. Checks with several tags are
+    /// part of each of their groups, and untagged checks come last.
+    pub(crate) fn groups(&self) -> Vec<(&str, Vec<&Check>)> {
+        let mut groups: BTreeMap<&str, Vec<&Check>> = BTreeMap::new();
+        let mut untagged = Vec::new();
+        for check in &self.checks {
+            let tags: Vec<&str> = check.tags.split_whitespace().collect();
+            match self.tag {
+                Some(ref tag) if tags.contains(&tag.as_str()) => {
+                    groups.entry(tag.as_str()).or_default().push(check);
+                }
+                Some(_) => {}
+                None if tags.is_empty() => untagged.push(check),
+                None => {
+                    for tag in tags {
+                        groups.entry(tag).or_default().push(check);
+                    }
+                }
+            }
+        }
+        let mut groups: Vec<_> = groups.into_iter().collect();
+        if !untagged.is_empty() {
+            groups.push((UNTAGGED, untagged));
+        }
+        for (_, checks) in &mut groups {
+            checks.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        groups
+    }
+
+    /// Checks in the order they are shown, which is the order they are selected in.
+    pub(crate) fn rows(&self) -> Vec<&Check> {
+        self.groups()
+            .into_iter()
+            .flat_map(|(_, checks)| checks)
+            .collect()
+    }
+}
+
+/// Identifier of a check that stays the same across refreshes, even for
+/// read-only API keys.
+fn key(check: &Check) -> String {
+    check
+        .id()
+        .or_else(|| check.unique_key.clone())
+        .unwrap_or_else(|| check.name.clone())
+}
+
+/// Compact form of a number of seconds, such as `45s`, `12m` or `3h 20m`.
+pub(crate) fn compact_duration(seconds: i64) -> String {
+    let seconds = seconds.abs();
+    match seconds {
+        0..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m", seconds / 60),
+        3600..86400 => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+        _ => format!("{}d {}h", seconds / 86400, seconds % 86400 / 3600),
+    }
+}
+
+/// How long ago `timestamp` was, or `-` if there is none.
+fn since(now: Timestamp, timestamp: Option<&str>) -> String {
+    match timestamp.and_then(|ts| ts.parse::<Timestamp>().ok()) {
+        Some(ts) => format!("{} ago", compact_duration(now.duration_since(ts).as_secs())),
+        None => "-".to_owned(),
+    }
+}
+
+/// How long until `timestamp`, or how long it has been overdue.
+fn until(now: Timestamp, timestamp: Option<&str>) -> String {
+    match timestamp.and_then(|ts| ts.parse::<Timestamp>().ok()) {
+        Some(ts) if ts >= now => {
+            format!("in {}", compact_duration(ts.duration_since(now).as_secs()))
+        }
+        Some(ts) => format!(
+            "{} overdue",
+            compact_duration(now.duration_since(ts).as_secs())
+        ),
+        None => "-".to_owned(),
+    }
+}
+
+fn status_color(status: &str) -> Color {
+    match status {
+        "up" => Color::Green,
+        "grace" => Color::Yellow,
+        "down" => Color::Red,
+        "started" => Color::Cyan,
+        _ => Color::DarkGrey,
+    }
+}
+
+/// Part of a line drawn with a single style.
+struct Span {
+    text: String,
+    color: Option<Color>,
+    bold: bool,
+}
+
+impl Span {
+    fn plain(text: impl Into<String>) -> Span {
+        Span {
+            text: text.into(),
+            color: None,
+            bold: false,
+        }
+    }
+
+    fn colored(text: impl Into<String>, color: Color) -> Span {
+        Span {
+            text: text.into(),
+            color: Some(color),
+            bold: false,
+        }
+    }
+
+    fn bold(text: impl Into<String>) -> Span {
+        Span {
+            text: text.into(),
+            color: None,
+            bold: true,
+        }
+    }
+}
+
+type Line = Vec<Span>;
+
+/// Pings and status changes of the check opened from the dashboard.
+struct Details {
+    name: String,
+    pings: Result<Vec<Ping>, String>,
+    flips: Result<Vec<Flip>, String>,
+}
+
+impl Details {
+    fn fetch(client: &ManageClient, check: &Check) -> Details {
+        let id = key(check);
+        Details {
+            name: check.name.clone(),
+            pings: client.list_logged_pings(&id).map_err(|e| e.to_string()),
+            flips: client.list_status_changes(&id).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn lines(&self) -> Vec<Line> {
+        let mut lines = vec![vec![Span::bold("Recent pings")]];
+        match self.pings {
+            Ok(ref pings) if pings.is_empty() => lines.push(vec![Span::plain("  none")]),
+            Ok(ref pings) => lines.extend(pings.iter().map(|ping| {
+                let color = match ping.type_field.as_str() {
+                    "success" => Color::Green,
+                    "fail" => Color::Red,
+                    "start" => Color::Cyan,
+                    _ => Color::DarkGrey,
+                };
+                let duration = ping
+                    .duration
+                    .map(|d| format!("{d:.3} sec"))
+                    .unwrap_or_default();
+                vec![
+                    Span::plain(format!("  #{:<6} {}  ", ping.n, ping.date)),
+                    Span::colored(format!("{:<8}", ping.type_field), color),
+                    Span::plain(format!(" {duration}")),
+                ]
+            })),
+            Err(ref error) => lines.push(vec![Span::colored(format!("  {error}"), Color::Red)]),
+        }
+        lines.push(Vec::new());
+        lines.push(vec![Span::bold("Status changes")]);
+        match self.flips {
+            Ok(ref flips) if flips.is_empty() => lines.push(vec![Span::plain("  none")]),
+            Ok(ref flips) => lines.extend(flips.iter().map(|flip| {
+                let (status, color) = if flip.up == 1 {
+                    ("up", Color::Green)
+                } else {
+                    ("down", Color::Red)
+                };
+                vec![
+                    Span::plain(format!("  {}  ", flip.timestamp)),
+                    Span::colored(status, color),
+                ]
+            })),
+            Err(ref error) => lines.push(vec![Span::colored(format!("  {error}"), Color::Red)]),
+        }
+        lines
+    }
+}
+
+/// Puts the terminal in raw mode on an alternate screen, restoring it when dropped.
+struct Screen {
+    stdout: Stdout,
+}
+
+impl Screen {
+    fn enter() -> Result<Screen> {
+        let mut stdout = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(stdout, EnterAlternateScreen, Hide)?;
+        Ok(Screen { stdout })
+    }
+
+    /// Draw the fixed `header` and `footer`, with as much of `body` as fits
+    /// between them, starting `scroll` lines in.
+    fn draw(&mut self, header: &[Line], body: &[Line], scroll: usize, footer: &Line) -> Result<()> {
+        let (width, height) = terminal::size()?;
+        let body_height = usize::from(height).saturating_sub(header.len() + 1);
+        queue!(self.stdout, Clear(ClearType::All))?;
+        let visible = header
+            .iter()
+            .chain(body.iter().skip(scroll).take(body_height));
+        for (row, line) in visible.enumerate() {
+            self.line(row, line, width)?;
+        }
+        self.line(usize::from(height.saturating_sub(1)), footer, width)?;
+        self.stdout.flush()?;
+        Ok(())
+    }
+
+    fn line(&mut self, row: usize, line: &Line, width: u16) -> Result<()> {
+        queue!(
+            self.stdout,
+            MoveTo(0, u16::try_from(row).unwrap_or(u16::MAX))
+        )?;
+        let mut remaining = usize::from(width);
+        for span in line {
+            let text: String = span.text.chars().take(remaining).collect();
+            remaining -= text.chars().count();
+            if let Some(color) = span.color {
+                queue!(self.stdout, SetForegroundColor(color))?;
+            }
+            if span.bold {
+                queue!(self.stdout, SetAttribute(Attribute::Bold))?;
+            }
+            queue!(
+                self.stdout,
+                Print(text),
+                SetAttribute(Attribute::Reset),
+                ResetColor
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(self.stdout, Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Lines of the dashboard, along with the index of the line of the selected check.
+fn dashboard_lines(dashboard: &Dashboard, selected: usize, now: Timestamp) -> (Vec<Line>, usize) {
+    let name_width = dashboard
+        .rows()
+        .iter()
+        .map(|check| check.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .clamp(4, 40);
+    let mut lines = Vec::new();
+    let mut selected_line = 0;
+    let mut index = 0;
+    for (tag, checks) in dashboard.groups() {
+        if !lines.is_empty() {
+            lines.push(Vec::new());
+        }
+        lines.push(vec![Span::bold(tag)]);
+        for check in checks {
+            let marker = if index == selected {
+                selected_line = lines.len();
+                "> "
+            } else {
+                "  "
+            };
+            let mut line = vec![
+                Span::plain(marker),
+                Span::colored(format!("{:<8}", check.status), status_color(&check.status)),
+                Span {
+                    text: format!(
+                        " {:<name_width$} {:>12} {:>16}",
+                        check.name,
+                        since(now, check.last_ping.as_deref()),
+                        until(now, check.next_ping.as_deref()),
+                    ),
+                    color: None,
+                    bold: index == selected,
+                },
+            ];
+            if let Some(previous) = dashboard.previous_status(check) {
+                line.push(Span {
+                    text: format!("  was {previous}"),
+                    color: Some(Color::Magenta),
+                    bold: true,
+                });
+            }
+            lines.push(line);
+            index += 1;
+        }
+    }
+    if lines.is_empty() {
+        lines.push(vec![Span::plain("No checks")]);
+    }
+    (lines, selected_line)
+}
+
+/// Show a dashboard of the checks, refreshed every `interval`, until the user quits.
+pub(crate) fn run(client: &ManageClient, interval: Duration, tag: Option<String>) -> Result<()> {
+    let mut dashboard = Dashboard::new(tag);
+    let mut details: Option<Details> = None;
+    let mut selected = 0;
+    let mut scroll = 0;
+    let mut status = String::from("Loading checks");
+    let mut next_refresh = Instant::now();
+    let mut screen = Screen::enter()?;
+    loop {
+        if Instant::now() >= next_refresh {
+            status = match client.get_checks() {
+                Ok(checks) => {
+                    dashboard.refresh(checks);
+                    format!(
+                        "Refreshed at {} UTC, every {}",
+                        Timestamp::now().strftime("%H:%M:%S"),
+                        compact_duration(i64::try_from(interval.as_secs())?)
+                    )
+                }
+                Err(error) => format!("Refresh failed: {error}"),
+            };
+            next_refresh = Instant::now() + interval;
+        }
+        let rows = dashboard.rows().len();
+        selected = selected.min(rows.saturating_sub(1));
+
+        let (_, height) = terminal::size()?;
+        let body_height = usize::from(height).saturating_sub(3).max(1);
+        let (header, body, footer) = match details {
+            Some(ref details) => (
+                vec![
+                    vec![Span::bold(format!("hcctl watch: {}", details.name))],
+                    Vec::new(),
+                ],
+                details.lines(),
+                "Esc back  j/k scroll  q quit",
+            ),
+            None => {
+                let (body, selected_line) = dashboard_lines(&dashboard, selected, Timestamp::now());
+                if selected_line < scroll {
+                    scroll = selected_line.saturating_sub(1);
+                } else if selected_line >= scroll + body_height {
+                    scroll = selected_line + 1 - body_height;
+                }
+                (
+                    vec![
+                        vec![Span::bold("hcctl watch  "), Span::plain(status.as_str())],
+                        Vec::new(),
+                    ],
+                    body,
+                    "Up/Down select  Enter details  r refresh  q quit",
+                )
+            }
+        };
+        screen.draw(
+            &header,
+            &body,
+            scroll,
+            &vec![Span::colored(footer, Color::DarkGrey)],
+        )?;
+
+        if !event::poll(next_refresh.saturating_duration_since(Instant::now()))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            KeyCode::Char('q') => return Ok(()),
+            KeyCode::Esc | KeyCode::Backspace if details.is_some() => {
+                details = None;
+                scroll = 0;
+            }
+            KeyCode::Esc => return Ok(()),
+            KeyCode::Up | KeyCode::Char('k') if details.is_some() => {
+                scroll = scroll.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if details.is_some() => {
+                scroll += 1;
+            }
+            KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                selected = (selected + 1).min(rows.saturating_sub(1));
+            }
+            KeyCode::Char('r') => next_refresh = Instant::now(),
+            KeyCode::Enter if details.is_none() => {
+                if let Some(check) = dashboard.rows().get(selected) {
+                    details = Some(Details::fetch(client, check));
+                    scroll = 0;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(name: &str, tags: &str, status: &str) -> Check {
+        crate::test_utils::check(name, tags, status, None)
+    }
+
+    #[test]
+    fn groups_checks_by_tag() {
+        let mut dashboard = Dashboard::new(None);
+        dashboard.refresh(vec![
+            check("vacuum", "prod db", "up"),
+            check("backup", "prod", "down"),
+            check("scratch", "", "new"),
+        ]);
+        let groups: Vec<(&str, Vec<&str>)> = dashboard
+            .groups()
+            .into_iter()
+            .map(|(tag, checks)| (tag, checks.iter().map(|c| c.name.as_str()).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                ("db", vec!["vacuum"]),
+                ("prod", vec!["backup", "vacuum"]),
+                (UNTAGGED, vec!["scratch"]),
+            ]
+        );
+        assert_eq!(dashboard.rows().len(), 4);
+
+        let mut dashboard = Dashboard::new(Some("db".to_owned()));
+        dashboard.refresh(vec![
+            check("vacuum", "prod db", "up"),
+            check("backup", "prod", "up"),
+        ]);
+        assert_eq!(dashboard.rows().len(), 1);
+    }
+
+    #[test]
+    fn tracks_transitions_between_refreshes() {
+        let mut dashboard = Dashboard::new(None);
+        dashboard.refresh(vec![check("backup", "", "up")]);
+        assert_eq!(dashboard.previous_status(&check("backup", "", "up")), None);
+
+        dashboard.refresh(vec![
+            check("backup", "", "down"),
+            check("vacuum", "", "new"),
+        ]);
+        assert_eq!(
+            dashboard.previous_status(&check("backup", "", "down")),
+            Some("up")
+        );
+        assert_eq!(dashboard.previous_status(&check("vacuum", "", "new")), None);
+
+        dashboard.refresh(vec![check("backup", "", "down")]);
+        assert_eq!(
+            dashboard.previous_status(&check("backup", "", "down")),
+            None
+        );
+    }
+
+    #[test]
+    fn formats_relative_times() {
+        let now: Timestamp = "2024-01-01T12:00:00+00:00".parse().unwrap();
+        assert_eq!(since(now, Some("2024-01-01T11:59:15+00:00")), "45s ago");
+        assert_eq!(since(now, None), "-");
+        assert_eq!(until(now, Some("2024-01-01T15:20:00+00:00")), "in 3h 20m");
+        assert_eq!(
+            until(now, Some("2023-12-30T11:00:00+00:00")),
+            "2d 1h overdue"
+        );
+        assert_eq!(compact_duration(720), "12m");
+    }
+}
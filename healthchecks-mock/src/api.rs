@@ -0,0 +1,550 @@
+use crate::http::{Request, Response};
+use crate::{
+    slugify, MockCheck, RecordedFlip, RecordedPing, State, API_KEY, PING_KEY, READ_ONLY_API_KEY,
+};
+use serde_json::{json, Map, Value};
+use std::sync::{Mutex, PoisonError};
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+/// Bounds for `timeout` and `grace` accepted by healthchecks, in seconds.
+const MIN_TIMEOUT: i64 = 60;
+const MAX_TIMEOUT: i64 = 31_536_000;
+
+/// Fields that can be used to match existing checks when creating a check.
+const UNIQUE_FIELDS: [&str; 5] = ["name", "tags", "timeout", "grace", "slug"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Access {
+    ReadWrite,
+    ReadOnly,
+}
+
+pub(crate) fn handle(request: &Request, base_url: &str, state: &Mutex<State>) -> Response {
+    let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(path) = request.path.strip_prefix("/ping/") {
+        return ping(request, path, &mut state);
+    }
+    let Some(rest) = request.path.strip_prefix("/api/v") else {
+        return Response::text(404, "not found");
+    };
+    let Some((version, path)) = rest.split_once('/') else {
+        return Response::text(404, "not found");
+    };
+    let version = match version {
+        "1" => 1,
+        "2" => 2,
+        "3" => 3,
+        _ => return Response::text(404, "not found"),
+    };
+    let access = match request.header("X-Api-Key") {
+        Some(API_KEY) => Access::ReadWrite,
+        Some(READ_ONLY_API_KEY) => Access::ReadOnly,
+        _ => return Response::error(401, "wrong api key"),
+    };
+    let ctx = Context {
+        version,
+        access,
+        base_url,
+    };
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["checks"]) => list_checks(request, &ctx, &state),
+        ("POST", ["checks"]) => ctx.require_write(|| create_check(request, &ctx, &mut state)),
+        ("GET", ["checks", id]) => with_check(&state, id, |c| Response::json(200, &ctx.check(c))),
+        ("POST", ["checks", id]) => {
+            ctx.require_write(|| update_check(request, &ctx, &mut state, id))
+        }
+        ("DELETE", ["checks", id]) => ctx.require_write(|| delete_check(&ctx, &mut state, id)),
+        ("POST", ["checks", id, "pause"]) => ctx.require_write(|| {
+            with_check_mut(&mut state, id, |c| {
+                if c.status != "paused" {
+                    c.flips.push(RecordedFlip {
+                        timestamp: now(),
+                        up: false,
+                    });
+                }
+                c.status = "paused".to_owned();
+                c.started = false;
+                c.last_start.clear();
+                Response::json(200, &ctx.check(c))
+            })
+        }),
+        ("POST", ["checks", id, "resume"]) => ctx.require_write(|| {
+            with_check_mut(&mut state, id, |c| {
+                if c.status == "paused" {
+                    c.flips.push(RecordedFlip {
+                        timestamp: now(),
+                        up: false,
+                    });
+                    c.status = "new".to_owned();
+                    c.last_ping = None;
+                }
+                Response::json(200, &ctx.check(c))
+            })
+        }),
+        ("GET", ["checks", id, "pings"]) => with_check(&state, id, |c| {
+            let pings: Vec<Value> = c.pings.iter().rev().map(|p| ctx.ping(c, p)).collect();
+            Response::json(200, &json!({ "pings": pings }))
+        }),
+        ("GET", ["checks", id, "pings", n, "body"]) if version >= 3 => {
+            with_check(&state, id, |c| {
+                match c.pings.iter().find(|p| p.n.to_string() == *n) {
+                    Some(ping) if !ping.body.is_empty() => Response::text(200, &ping.body),
+                    _ => Response::text(404, "not found"),
+                }
+            })
+        }
+        ("GET", ["checks", id, "flips"]) => with_check(&state, id, |c| {
+            let flips: Vec<Value> = c
+                .flips
+                .iter()
+                .rev()
+                .map(|f| json!({ "timestamp": date(f.timestamp), "up": i64::from(f.up) }))
+                .collect();
+            Response::json(200, &json!({ "flips": flips }))
+        }),
+        ("GET", ["channels"]) => ctx.require_write(|| {
+            let channels: Vec<Value> = state
+                .channels
+                .iter()
+                .map(|c| json!({ "id": c.id, "name": c.name, "kind": c.kind }))
+                .collect();
+            Response::json(200, &json!({ "channels": channels }))
+        }),
+        _ => Response::text(405, "method not allowed"),
+    }
+}
+
+struct Context<'a> {
+    version: u8,
+    access: Access,
+    base_url: &'a str,
+}
+
+impl Context<'_> {
+    /// Run `f` only if the API key allows writes, which the channels endpoint also requires.
+    fn require_write<F: FnOnce() -> Response>(&self, f: F) -> Response {
+        if self.access == Access::ReadWrite {
+            f()
+        } else {
+            Response::error(401, "wrong api key")
+        }
+    }
+
+    /// Serialize a check the way the given API version and key would see it.
+    fn check(&self, check: &MockCheck) -> Value {
+        let mut value = json!({
+            "name": check.name,
+            "tags": check.tags,
+            "desc": check.desc,
+            "grace": check.grace,
+            "n_pings": check.pings.len(),
+            "status": check.status,
+            "last_ping": check.last_ping.map(date),
+            "next_ping": match (check.last_ping, check.timeout) {
+                (Some(last_ping), Some(timeout)) => Some(date(last_ping + Duration::seconds(timeout))),
+                _ => None,
+            },
+            "manual_resume": check.manual_resume,
+            "methods": "",
+        });
+        let object = value
+            .as_object_mut()
+            .expect("check is serialized as an object");
+        if self.version >= 2 {
+            object.insert("started".to_owned(), json!(check.started));
+        }
+        if self.version >= 3 {
+            object.insert("slug".to_owned(), json!(check.slug));
+        }
+        match check.schedule {
+            Some(ref schedule) => {
+                object.insert("schedule".to_owned(), json!(schedule));
+                object.insert("tz".to_owned(), json!(check.tz.as_deref().unwrap_or("UTC")));
+            }
+            None => {
+                object.insert("timeout".to_owned(), json!(check.timeout));
+            }
+        }
+        if self.access == Access::ReadWrite {
+            let api = format!(
+                "{}/api/v{}/checks/{}",
+                self.base_url, self.version, check.uuid
+            );
+            object.insert(
+                "ping_url".to_owned(),
+                json!(format!("{}/ping/{}", self.base_url, check.uuid)),
+            );
+            object.insert("update_url".to_owned(), json!(api));
+            object.insert("pause_url".to_owned(), json!(format!("{api}/pause")));
+            object.insert("resume_url".to_owned(), json!(format!("{api}/resume")));
+            object.insert("channels".to_owned(), json!(check.channels.join(",")));
+        } else {
+            object.insert("unique_key".to_owned(), json!(check.unique_key()));
+        }
+        value
+    }
+
+    /// Serialize a ping the way the given API version would see it.
+    fn ping(&self, check: &MockCheck, ping: &RecordedPing) -> Value {
+        let mut value = json!({
+            "type": ping.kind,
+            "date": date(ping.date),
+            "n": ping.n,
+            "scheme": "http",
+            "remote_addr": "127.0.0.1",
+            "method": ping.method,
+            "ua": ping.user_agent,
+        });
+        let object = value
+            .as_object_mut()
+            .expect("ping is serialized as an object");
+        if let Some(duration) = ping.duration {
+            object.insert("duration".to_owned(), json!(duration));
+        }
+        if self.version >= 3 {
+            object.insert("rid".to_owned(), json!(ping.run_id));
+            if let Some(exit_status) = ping.exit_status {
+                object.insert("exitstatus".to_owned(), json!(exit_status));
+            }
+            if !ping.body.is_empty() {
+                object.insert(
+                    "body_url".to_owned(),
+                    json!(format!(
+                        "{}/api/v{}/checks/{}/pings/{}/body",
+                        self.base_url, self.version, check.uuid, ping.n
+                    )),
+                );
+            }
+        }
+        value
+    }
+}
+
+fn date(date: OffsetDateTime) -> String {
+    date.format(&Rfc3339)
+        .expect("UTC timestamps can always be formatted")
+}
+
+fn now() -> OffsetDateTime {
+    let now = OffsetDateTime::now_utc();
+    now.replace_nanosecond(0).unwrap_or(now)
+}
+
+fn find_check<'a>(state: &'a State, id: &str) -> Option<&'a MockCheck> {
+    state
+        .checks
+        .iter()
+        .find(|c| c.uuid == id || c.unique_key() == id)
+}
+
+fn with_check<F: FnOnce(&MockCheck) -> Response>(state: &State, id: &str, f: F) -> Response {
+    match find_check(state, id) {
+        Some(check) => f(check),
+        None => Response::error(404, "not found"),
+    }
+}
+
+fn with_check_mut<F: FnOnce(&mut MockCheck) -> Response>(
+    state: &mut State,
+    id: &str,
+    f: F,
+) -> Response {
+    match state.checks.iter_mut().find(|c| c.uuid == id) {
+        Some(check) => f(check),
+        None => Response::error(404, "not found"),
+    }
+}
+
+fn list_checks(request: &Request, ctx: &Context<'_>, state: &State) -> Response {
+    let tags: Vec<&str> = request.query_all("tag").collect();
+    let slug = if ctx.version >= 3 {
+        request.query("slug")
+    } else {
+        None
+    };
+    let checks: Vec<Value> = state
+        .checks
+        .iter()
+        .filter(|c| {
+            tags.iter()
+                .all(|tag| c.tags.split_whitespace().any(|t| t == *tag))
+        })
+        .filter(|c| slug.is_none_or(|slug| c.slug == slug))
+        .map(|c| ctx.check(c))
+        .collect();
+    Response::json(200, &json!({ "checks": checks }))
+}
+
+fn create_check(request: &Request, ctx: &Context<'_>, state: &mut State) -> Response {
+    let spec = match parse_spec(request, ctx, state) {
+        Ok(spec) => spec,
+        Err(response) => return response,
+    };
+    let mut candidate = MockCheck::new("");
+    if let Err(response) = apply_spec(&mut candidate, &spec, state) {
+        return response;
+    }
+    if let Some(Value::Array(unique)) = spec.get("unique") {
+        let existing = state.checks.iter().position(|c| {
+            unique
+                .iter()
+                .filter_map(Value::as_str)
+                .all(|field| match field {
+                    "name" => c.name == candidate.name,
+                    "tags" => c.tags == candidate.tags,
+                    "timeout" => c.timeout == candidate.timeout,
+                    "grace" => c.grace == candidate.grace,
+                    "slug" => c.slug == candidate.slug,
+                    _ => false,
+                })
+        });
+        if let Some(idx) = existing {
+            let mut check = state.checks[idx].clone();
+            if let Err(response) = apply_spec(&mut check, &spec, state) {
+                return response;
+            }
+            let response = Response::json(200, &ctx.check(&check));
+            state.checks[idx] = check;
+            return response;
+        }
+    }
+    if state
+        .check_limit
+        .is_some_and(|limit| state.checks.len() >= limit)
+    {
+        return Response::error(403, "checks limit exceeded");
+    }
+    let response = Response::json(201, &ctx.check(&candidate));
+    state.checks.push(candidate);
+    response
+}
+
+fn update_check(request: &Request, ctx: &Context<'_>, state: &mut State, id: &str) -> Response {
+    let spec = match parse_spec(request, ctx, state) {
+        Ok(spec) => spec,
+        Err(response) => return response,
+    };
+    let Some(idx) = state.checks.iter().position(|c| c.uuid == id) else {
+        return Response::error(404, "not found");
+    };
+    let mut check = state.checks[idx].clone();
+    if let Err(response) = apply_spec(&mut check, &spec, state) {
+        return response;
+    }
+    let response = Response::json(200, &ctx.check(&check));
+    state.checks[idx] = check;
+    response
+}
+
+fn delete_check(ctx: &Context<'_>, state: &mut State, id: &str) -> Response {
+    match state.checks.iter().position(|c| c.uuid == id) {
+        Some(idx) => {
+            let check = state.checks.remove(idx);
+            Response::json(200, &ctx.check(&check))
+        }
+        None => Response::error(404, "not found"),
+    }
+}
+
+/// Parse and validate the JSON body of a create or update request.
+fn parse_spec(
+    request: &Request,
+    ctx: &Context<'_>,
+    state: &State,
+) -> Result<Map<String, Value>, Response> {
+    let body = if request.body.is_empty() {
+        b"{}".as_slice()
+    } else {
+        request.body.as_slice()
+    };
+    let Ok(Value::Object(spec)) = serde_json::from_slice::<Value>(body) else {
+        return Err(Response::error(400, "could not parse request body"));
+    };
+    let invalid = |field: &str| {
+        Err(Response::error(
+            400,
+            &format!("json validation error: {field}"),
+        ))
+    };
+    for (key, value) in &spec {
+        let valid = match key.as_str() {
+            "name" => value.as_str().is_some_and(|v| v.chars().count() <= 100),
+            "slug" => value.as_str().is_some_and(|v| {
+                v.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            }),
+            "tags" => value.as_str().is_some_and(|v| v.chars().count() <= 500),
+            "desc" | "methods" => value.is_string(),
+            "timeout" | "grace" => value
+                .as_i64()
+                .is_some_and(|v| (MIN_TIMEOUT..=MAX_TIMEOUT).contains(&v)),
+            "schedule" => value
+                .as_str()
+                .is_some_and(|v| !v.trim().is_empty() && v.chars().count() <= 100),
+            "tz" => value
+                .as_str()
+                .is_some_and(|v| !v.is_empty() && v.len() <= 36),
+            "manual_resume" => value.is_boolean(),
+            "channels" => value
+                .as_str()
+                .is_some_and(|v| resolve_channels(v, state).is_some()),
+            "unique" => value.as_array().is_some_and(|fields| {
+                fields.iter().all(|field| {
+                    field.as_str().is_some_and(|field| {
+                        UNIQUE_FIELDS.contains(&field) && (field != "slug" || ctx.version >= 3)
+                    })
+                })
+            }),
+            _ => true,
+        };
+        if !valid {
+            return invalid(key);
+        }
+    }
+    Ok(spec)
+}
+
+/// Apply a validated spec to `check`, resolving channel names against `state`.
+fn apply_spec(
+    check: &mut MockCheck,
+    spec: &Map<String, Value>,
+    state: &State,
+) -> Result<(), Response> {
+    let string = |key: &str| spec.get(key).and_then(Value::as_str).map(ToOwned::to_owned);
+    if let Some(name) = string("name") {
+        if !spec.contains_key("slug") {
+            check.slug = slugify(&name);
+        }
+        check.name = name;
+    }
+    if let Some(slug) = string("slug") {
+        check.slug = slug;
+    }
+    if let Some(tags) = string("tags") {
+        check.tags = tags;
+    }
+    if let Some(desc) = string("desc") {
+        check.desc = desc;
+    }
+    if let Some(grace) = spec.get("grace").and_then(Value::as_i64) {
+        check.grace = grace;
+    }
+    if let Some(schedule) = string("schedule") {
+        check.schedule = Some(schedule);
+        check.tz = Some(string("tz").unwrap_or_else(|| "UTC".to_owned()));
+        check.timeout = None;
+    } else if let Some(timeout) = spec.get("timeout").and_then(Value::as_i64) {
+        check.timeout = Some(timeout);
+        check.schedule = None;
+        check.tz = None;
+    } else if let Some(tz) = string("tz") {
+        check.tz = Some(tz);
+    }
+    if let Some(manual_resume) = spec.get("manual_resume").and_then(Value::as_bool) {
+        check.manual_resume = manual_resume;
+    }
+    if let Some(channels) = string("channels") {
+        check.channels = resolve_channels(&channels, state)
+            .ok_or_else(|| Response::error(400, "invalid channel identifier"))?;
+    }
+    Ok(())
+}
+
+/// Resolve the `channels` field: `*` for all channels, an empty string for none,
+/// or a comma-separated list of channel IDs or names.
+fn resolve_channels(channels: &str, state: &State) -> Option<Vec<String>> {
+    match channels.trim() {
+        "" => Some(Vec::new()),
+        "*" => Some(state.channels.iter().map(|c| c.id.clone()).collect()),
+        list => list
+            .split(',')
+            .map(str::trim)
+            .map(|item| {
+                state
+                    .channels
+                    .iter()
+                    .find(|c| c.id == item || c.name == item)
+                    .map(|c| c.id.clone())
+            })
+            .collect(),
+    }
+}
+
+fn ping(request: &Request, path: &str, state: &mut State) -> Response {
+    if !matches!(request.method.as_str(), "GET" | "POST" | "HEAD") {
+        return Response::text(405, "method not allowed");
+    }
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let (check, action) = match segments.as_slice() {
+        [uuid, action @ ..] if Uuid::parse_str(uuid).is_ok() => {
+            (state.checks.iter_mut().find(|c| c.uuid == *uuid), action)
+        }
+        [PING_KEY, slug, action @ ..] => {
+            (state.checks.iter_mut().find(|c| c.slug == *slug), action)
+        }
+        _ => return Response::text(404, "not found"),
+    };
+    let Some(check) = check else {
+        return Response::text(404, "not found");
+    };
+    let (kind, exit_status) = match action {
+        [] => ("success", None),
+        ["fail"] => ("fail", None),
+        ["start"] => ("start", None),
+        ["log"] => ("log", None),
+        [code] => match code.parse::<u8>() {
+            Ok(0) => ("success", Some(0)),
+            Ok(code) => ("fail", Some(code)),
+            Err(_) => return Response::text(404, "not found"),
+        },
+        _ => return Response::text(404, "not found"),
+    };
+    let run_id = request.query("rid").map(ToOwned::to_owned);
+    if run_id
+        .as_deref()
+        .is_some_and(|rid| Uuid::parse_str(rid).is_err())
+    {
+        return Response::text(400, "invalid uuid format");
+    }
+
+    let now = now();
+    let mut duration = None;
+    match kind {
+        "start" => {
+            check.started = true;
+            check.last_start.insert(run_id.clone(), now);
+        }
+        "success" | "fail" => {
+            if let Some(start) = check.last_start.remove(&run_id) {
+                duration = Some((now - start).as_seconds_f64());
+            }
+            check.started = !check.last_start.is_empty();
+            check.last_ping = Some(now);
+            let paused = check.status == "paused" && check.manual_resume;
+            let new_status = if kind == "success" { "up" } else { "down" };
+            if !paused && check.status != new_status {
+                check.flips.push(RecordedFlip {
+                    timestamp: now,
+                    up: new_status == "up",
+                });
+                new_status.clone_into(&mut check.status);
+            }
+        }
+        _ => {}
+    }
+    check.pings.push(RecordedPing {
+        n: i64::try_from(check.pings.len()).unwrap_or(i64::MAX) + 1,
+        kind: kind.to_owned(),
+        date: now,
+        method: request.method.clone(),
+        user_agent: request.header("User-Agent").unwrap_or_default().to_owned(),
+        body: String::from_utf8_lossy(&request.body).into_owned(),
+        run_id,
+        exit_status,
+        duration,
+    });
+    Response::text(200, "OK")
+}
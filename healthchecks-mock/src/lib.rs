@@ -0,0 +1,351 @@
+#![doc = include_str!("../README.md")]
+/// Request handlers for the pinging and management APIs.
+mod api;
+/// Minimal HTTP/1.1 parsing and serialization.
+mod http;
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// API key with read-write access to the mock project.
+pub const API_KEY: &str = "mock-api-key";
+/// API key with read-only access to the mock project.
+pub const READ_ONLY_API_KEY: &str = "mock-read-only-api-key";
+/// Ping key of the mock project, used to ping checks by slug.
+pub const PING_KEY: &str = "mock-ping-key";
+
+/// A failure to simulate for a single request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// Wait for the given duration before handling the request normally.
+    Latency(Duration),
+    /// Respond with the given HTTP status code, such as 500 or 503.
+    Status(u16),
+    /// Respond with 429 Too Many Requests and a `Retry-After` header.
+    RateLimited,
+    /// Close the connection without sending a response.
+    DropConnection,
+}
+
+/// A check stored by the mock server.
+#[derive(Clone, Debug)]
+pub struct MockCheck {
+    /// UUID of the check.
+    pub uuid: String,
+    /// Name of the check.
+    pub name: String,
+    /// Slug of the check, used for slug-based pinging.
+    pub slug: String,
+    /// Space separated list of tags.
+    pub tags: String,
+    /// Description of the check.
+    pub desc: String,
+    /// Expected time between pings, in seconds. [`None`] for checks with a schedule.
+    pub timeout: Option<i64>,
+    /// Grace period, in seconds.
+    pub grace: i64,
+    /// Cron expression for checks with a schedule.
+    pub schedule: Option<String>,
+    /// Timezone for the schedule.
+    pub tz: Option<String>,
+    /// Whether a paused check stays paused when pinged.
+    pub manual_resume: bool,
+    /// IDs of the channels assigned to the check.
+    pub channels: Vec<String>,
+    /// Current status, one of `new`, `up`, `down` or `paused`.
+    pub status: String,
+    /// Whether a start ping was received without a matching finish ping.
+    pub started: bool,
+    /// Time of the last success or failure ping.
+    pub last_ping: Option<OffsetDateTime>,
+    /// Every ping received by the check, oldest first.
+    pub pings: Vec<RecordedPing>,
+    /// Every status change of the check, oldest first.
+    pub flips: Vec<RecordedFlip>,
+    /// Time of the last start ping for each run ID.
+    pub(crate) last_start: HashMap<Option<String>, OffsetDateTime>,
+}
+
+impl MockCheck {
+    /// Create a new check with the given name and the same defaults as healthchecks:
+    /// a one day timeout and one hour grace period.
+    #[must_use]
+    pub fn new(name: &str) -> MockCheck {
+        MockCheck {
+            uuid: Uuid::new_v4().to_string(),
+            name: name.to_owned(),
+            slug: slugify(name),
+            tags: String::new(),
+            desc: String::new(),
+            timeout: Some(86400),
+            grace: 3600,
+            schedule: None,
+            tz: None,
+            manual_resume: false,
+            channels: Vec::new(),
+            status: "new".to_owned(),
+            started: false,
+            last_ping: None,
+            pings: Vec::new(),
+            flips: Vec::new(),
+            last_start: HashMap::new(),
+        }
+    }
+
+    /// Stable identifier exposed instead of the UUID to read-only API keys.
+    #[must_use]
+    pub fn unique_key(&self) -> String {
+        self.uuid.replace('-', "").chars().rev().collect()
+    }
+}
+
+/// A ping received by the mock server.
+#[derive(Clone, Debug)]
+pub struct RecordedPing {
+    /// Index of the ping, starting at 1.
+    pub n: i64,
+    /// Type of the ping: `success`, `fail`, `start` or `log`.
+    pub kind: String,
+    /// Time at which the ping was received.
+    pub date: OffsetDateTime,
+    /// HTTP method of the ping request.
+    pub method: String,
+    /// User-Agent header of the ping request.
+    pub user_agent: String,
+    /// Body of the ping request.
+    pub body: String,
+    /// Run ID passed with the `rid` query parameter.
+    pub run_id: Option<String>,
+    /// Exit status for pings sent to the exit code endpoint.
+    pub exit_status: Option<u8>,
+    /// Time since the matching start ping, in seconds.
+    pub duration: Option<f64>,
+}
+
+/// A change in status recorded by the mock server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedFlip {
+    /// Time of the change.
+    pub timestamp: OffsetDateTime,
+    /// Whether the check went up or down.
+    pub up: bool,
+}
+
+/// A notification channel in the mock project.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MockChannel {
+    /// UUID of the channel.
+    pub id: String,
+    /// Display name of the channel.
+    pub name: String,
+    /// Kind of integration, such as `email`.
+    pub kind: String,
+}
+
+/// In-memory state of the mock project.
+#[derive(Default)]
+pub(crate) struct State {
+    pub(crate) checks: Vec<MockCheck>,
+    pub(crate) channels: Vec<MockChannel>,
+    pub(crate) faults: VecDeque<Fault>,
+    pub(crate) check_limit: Option<usize>,
+    pub(crate) requests: usize,
+}
+
+/// An HTTP server running on a random local port in a background thread, which
+/// is shut down when dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl MockServer {
+    /// Start a server with an empty project.
+    ///
+    /// # Panics
+    /// Panics if binding to a local port fails.
+    #[must_use]
+    pub fn start() -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind mock server");
+        let addr = listener
+            .local_addr()
+            .expect("failed to get mock server address");
+        let state = Arc::new(Mutex::new(State::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let server_state = Arc::clone(&state);
+        let server_shutdown = Arc::clone(&shutdown);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if server_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let state = Arc::clone(&server_state);
+                    thread::spawn(move || handle_connection(stream, addr, &state));
+                }
+            }
+        });
+        MockServer {
+            addr,
+            state,
+            shutdown,
+        }
+    }
+
+    /// Base URL of the server, such as `http://127.0.0.1:38121`.
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Base URL of the pinging API, to be used in place of `https://hc-ping.com`.
+    #[must_use]
+    pub fn ping_url(&self) -> String {
+        format!("{}/ping", self.url())
+    }
+
+    /// Base URL of the given version of the management API, to be used in place
+    /// of `https://healthchecks.io/api/v3`.
+    #[must_use]
+    pub fn api_url(&self, version: u8) -> String {
+        format!("{}/api/v{version}", self.url())
+    }
+
+    /// Add a check to the project, returning its UUID.
+    pub fn add_check(&self, check: MockCheck) -> String {
+        let uuid = check.uuid.clone();
+        self.lock().checks.push(check);
+        uuid
+    }
+
+    /// Add a channel to the project, returning its ID.
+    pub fn add_channel(&self, name: &str, kind: &str) -> String {
+        let id = Uuid::new_v4().to_string();
+        self.lock().channels.push(MockChannel {
+            id: id.clone(),
+            name: name.to_owned(),
+            kind: kind.to_owned(),
+        });
+        id
+    }
+
+    /// Get the check with the given UUID.
+    #[must_use]
+    pub fn check(&self, uuid: &str) -> Option<MockCheck> {
+        self.lock().checks.iter().find(|c| c.uuid == uuid).cloned()
+    }
+
+    /// Get every check in the project.
+    #[must_use]
+    pub fn checks(&self) -> Vec<MockCheck> {
+        self.lock().checks.clone()
+    }
+
+    /// Get the pings received by the check with the given UUID, oldest first.
+    #[must_use]
+    pub fn pings_for(&self, uuid: &str) -> Vec<RecordedPing> {
+        self.check(uuid).map(|c| c.pings).unwrap_or_default()
+    }
+
+    /// Get the status changes of the check with the given UUID, oldest first.
+    #[must_use]
+    pub fn flips_for(&self, uuid: &str) -> Vec<RecordedFlip> {
+        self.check(uuid).map(|c| c.flips).unwrap_or_default()
+    }
+
+    /// Number of requests received so far, including those affected by faults.
+    #[must_use]
+    pub fn request_count(&self) -> usize {
+        self.lock().requests
+    }
+
+    /// Queue a fault to apply to the next request that doesn't already have one.
+    pub fn inject(&self, fault: Fault) {
+        self.lock().faults.push_back(fault);
+    }
+
+    /// Limit the number of checks in the project, after which creating checks fails.
+    pub fn set_check_limit(&self, limit: Option<usize>) {
+        self.lock().check_limit = limit;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake up the accept loop so it notices the shutdown
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+fn handle_connection(stream: TcpStream, addr: SocketAddr, state: &Mutex<State>) {
+    let mut reader = BufReader::new(match stream.try_clone() {
+        Ok(stream) => stream,
+        Err(_) => return,
+    });
+    let Ok(Some(request)) = http::read_request(&mut reader) else {
+        return;
+    };
+    let fault = {
+        let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.requests += 1;
+        state.faults.pop_front()
+    };
+    let response = match fault {
+        Some(Fault::DropConnection) => {
+            let _ = stream.shutdown(Shutdown::Both);
+            return;
+        }
+        Some(Fault::Status(status)) => http::Response::text(status, "injected fault"),
+        Some(Fault::RateLimited) => {
+            let mut response = http::Response::text(429, "rate limited");
+            response.headers.push(("Retry-After", "1".to_owned()));
+            response
+        }
+        Some(Fault::Latency(latency)) => {
+            thread::sleep(latency);
+            api::handle(&request, &format!("http://{addr}"), state)
+        }
+        None => api::handle(&request, &format!("http://{addr}"), state),
+    };
+    let mut stream = stream;
+    let _ = http::write_response(&mut stream, &response);
+    let _ = stream.flush();
+}
+
+/// Generate a slug from a check name the same way healthchecks does.
+pub(crate) fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            slug.push(c);
+        } else if (c.is_whitespace() || c == '-') && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::slugify;
+
+    #[test]
+    fn slugifies_names() {
+        assert_eq!(slugify("Nightly Backup"), "nightly-backup");
+        assert_eq!(slugify("  db -- vacuum! "), "db-vacuum");
+        assert_eq!(slugify("snake_case"), "snake_case");
+    }
+}
@@ -0,0 +1,266 @@
+use crate::ping::{PingClient, PingKind};
+use std::collections::HashMap;
+use std::io;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// A ping waiting to be delivered by the worker thread.
+struct QueuedPing {
+    client: Arc<PingClient>,
+    kind: PingKind,
+    body: Option<String>,
+    run_id: Option<Uuid>,
+    /// Key the ping was recorded under for coalescing, if it is a plain success ping.
+    coalesce_key: Option<String>,
+}
+
+/// When the last plain success ping was queued for each check, keyed by the
+/// ping API URL and UUID of the check.
+type LastSuccess = Mutex<HashMap<String, Instant>>;
+
+/// Number of pings that have been accepted but not yet delivered, used to
+/// implement [`BackgroundPinger::flush`].
+#[derive(Default)]
+struct InFlight {
+    count: Mutex<usize>,
+    done: Condvar,
+}
+
+impl InFlight {
+    fn increment(&self) {
+        *self.count.lock().unwrap_or_else(PoisonError::into_inner) += 1;
+    }
+
+    fn decrement(&self) {
+        let mut count = self.count.lock().unwrap_or_else(PoisonError::into_inner);
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            self.done.notify_all();
+        }
+    }
+}
+
+/// Delivers pings from a dedicated worker thread so callers never block on the
+/// network. Pings are accepted from any number of threads into a bounded queue,
+/// and repeated success pings for the same check are coalesced within a
+/// configurable window.
+///
+/// Dropping the pinger lets the worker finish delivering queued pings in the
+/// background; call [`flush`](BackgroundPinger::flush) before shutting down to
+/// wait for them.
+///
+/// ```rust
+/// # use healthchecks::background::BackgroundPinger;
+/// # use healthchecks::ping::get_client;
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// #
+/// let pinger = BackgroundPinger::spawn(64, Duration::from_secs(60)).unwrap();
+/// let client = Arc::new(get_client("2d0a34bd-854d-490e-be2c-1493f7053460").unwrap());
+/// pinger.report_success(&client);
+/// pinger.flush(Duration::from_secs(5));
+/// ```
+pub struct BackgroundPinger {
+    sender: SyncSender<QueuedPing>,
+    in_flight: Arc<InFlight>,
+    coalesce_window: Duration,
+    last_success: Arc<LastSuccess>,
+}
+
+impl BackgroundPinger {
+    /// Start a worker thread that delivers up to `capacity` queued pings.
+    /// Success pings for a check are dropped if another one was queued less than
+    /// `coalesce_window` ago, unless a ping of another kind was queued for the
+    /// check in between or the earlier one could not be delivered; pass
+    /// [`Duration::ZERO`] to disable coalescing.
+    ///
+    /// # Errors
+    /// - Returns an [`io::Error`] if the worker thread could not be spawned.
+    pub fn spawn(capacity: usize, coalesce_window: Duration) -> io::Result<BackgroundPinger> {
+        let (sender, receiver) = sync_channel(capacity.max(1));
+        let in_flight = Arc::new(InFlight::default());
+        let last_success = Arc::new(LastSuccess::default());
+        let worker_in_flight = Arc::clone(&in_flight);
+        let worker_last_success = Arc::clone(&last_success);
+        thread::Builder::new()
+            .name("healthchecks-pinger".to_owned())
+            .spawn(move || work(&receiver, &worker_in_flight, &worker_last_success))?;
+        Ok(BackgroundPinger {
+            sender,
+            in_flight,
+            coalesce_window,
+            last_success,
+        })
+    }
+
+    /// Queue a success ping for `client`. Returns a boolean indicating whether the
+    /// ping was accepted, which is `false` when the queue is full.
+    #[must_use]
+    pub fn report_success(&self, client: &Arc<PingClient>) -> bool {
+        self.send(client, PingKind::Success, None, None)
+    }
+
+    /// Queue a failure ping for `client`. Returns a boolean indicating whether the
+    /// ping was accepted, which is `false` when the queue is full.
+    #[must_use]
+    pub fn report_failure(&self, client: &Arc<PingClient>) -> bool {
+        self.send(client, PingKind::Fail, None, None)
+    }
+
+    /// Queue a failure ping with an accompanying log snippet for `client`. Returns
+    /// a boolean indicating whether the ping was accepted, which is `false` when the
+    /// queue is full.
+    #[must_use]
+    pub fn report_failure_with_logs(&self, client: &Arc<PingClient>, data: &str) -> bool {
+        self.send(client, PingKind::Fail, Some(data.to_owned()), None)
+    }
+
+    /// Queue a start ping for `client`. Returns a boolean indicating whether the
+    /// ping was accepted, which is `false` when the queue is full.
+    #[must_use]
+    pub fn start_timer(&self, client: &Arc<PingClient>) -> bool {
+        self.send(client, PingKind::Start, None, None)
+    }
+
+    /// Queue a ping of any kind for `client`, optionally with a body and a run ID.
+    /// Returns a boolean indicating whether the ping was accepted, which is `false`
+    /// when the queue is full.
+    #[must_use]
+    pub fn send(
+        &self,
+        client: &Arc<PingClient>,
+        kind: PingKind,
+        body: Option<String>,
+        run_id: Option<Uuid>,
+    ) -> bool {
+        // Only plain success pings are interchangeable, anything carrying a body or
+        // a run ID means something to the server.
+        let key = format!("{}/{}", client.api_url, client.uuid);
+        let coalescable = kind == PingKind::Success && body.is_none() && run_id.is_none();
+        {
+            let mut last_success = self
+                .last_success
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if coalescable {
+                let now = Instant::now();
+                if should_coalesce(last_success.get(&key), now, self.coalesce_window) {
+                    return true;
+                }
+                last_success.insert(key.clone(), now);
+            } else {
+                // A success after a failure or start changes the state of the
+                // check again, so it must not be coalesced with the one before
+                last_success.remove(&key);
+            }
+        }
+        let coalesce_key = coalescable.then_some(key);
+        self.in_flight.increment();
+        let ping = QueuedPing {
+            client: Arc::clone(client),
+            kind,
+            body,
+            run_id,
+            coalesce_key: coalesce_key.clone(),
+        };
+        match self.sender.try_send(ping) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_) | TrySendError::Disconnected(_)) => {
+                self.in_flight.decrement();
+                // Don't swallow the next success ping in place of this dropped one
+                if let Some(key) = coalesce_key {
+                    self.last_success
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .remove(&key);
+                }
+                false
+            }
+        }
+    }
+
+    /// Wait for up to `timeout` for all queued pings to be delivered. Returns a
+    /// boolean indicating whether the queue was fully drained.
+    #[must_use]
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let count = self
+            .in_flight
+            .count
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let (count, _) = self
+            .in_flight
+            .done
+            .wait_timeout_while(count, timeout, |count| *count > 0)
+            .unwrap_or_else(PoisonError::into_inner);
+        *count == 0
+    }
+}
+
+fn work(receiver: &Receiver<QueuedPing>, in_flight: &InFlight, last_success: &LastSuccess) {
+    while let Ok(ping) = receiver.recv() {
+        // Delivery failures are already handled by the client's retries and spool,
+        // but the next success ping must not be coalesced with one that was lost
+        let delivered = ping
+            .client
+            .send(ping.kind, ping.body.as_deref(), ping.run_id.as_ref());
+        if let (false, Some(key)) = (delivered, ping.coalesce_key) {
+            last_success
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&key);
+        }
+        in_flight.decrement();
+    }
+}
+
+/// Whether a success ping should be dropped because another one was queued
+/// within `window`.
+fn should_coalesce(last: Option<&Instant>, now: Instant, window: Duration) -> bool {
+    last.is_some_and(|last| now.duration_since(*last) < window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ping::get_client_with_url;
+
+    fn unreachable_client() -> Arc<PingClient> {
+        Arc::new(
+            get_client_with_url("2d0a34bd-854d-490e-be2c-1493f7053460", "http://127.0.0.1:1")
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn coalesces_within_window() {
+        let now = Instant::now();
+        let window = Duration::from_secs(60);
+        assert!(!should_coalesce(None, now, window));
+        assert!(should_coalesce(
+            Some(&now),
+            now + Duration::from_secs(59),
+            window
+        ));
+        assert!(!should_coalesce(
+            Some(&now),
+            now + Duration::from_secs(60),
+            window
+        ));
+        assert!(!should_coalesce(Some(&now), now, Duration::ZERO));
+    }
+
+    #[test]
+    fn flush_waits_for_queued_pings() {
+        let pinger = BackgroundPinger::spawn(4, Duration::from_secs(60)).unwrap();
+        let client = unreachable_client();
+        assert!(pinger.report_success(&client));
+        assert!(pinger.report_success(&client));
+        assert!(pinger.report_failure(&client));
+        assert!(pinger.flush(Duration::from_secs(30)));
+        assert_eq!(*pinger.in_flight.count.lock().unwrap(), 0);
+    }
+}
@@ -0,0 +1,124 @@
+//! Environment variables read by [`PingClient::from_env`], [`ManageClient::from_env`] and
+//! [`PingRegistry::from_env`].
+//!
+//! | Variable                     | Used by                         | Description                                                        |
+//! |------------------------------|---------------------------------|--------------------------------------------------------------------|
+//! | `HEALTHCHECKS_PING_URL`      | [`PingClient`]                  | Full ping URL of the check, takes precedence over the next three   |
+//! | `HEALTHCHECKS_CHECK_ID`      | [`PingClient`]                  | UUID or slug of the check                                          |
+//! | `HEALTHCHECKS_PING_KEY`      | [`PingClient`], [`PingRegistry`]| Ping key of the project, required when checks are given by slug    |
+//! | `HEALTHCHECKS_PING_API_URL`  | [`PingClient`]                  | Base URL of the pinging API, defaults to `https://hc-ping.com`     |
+//! | `HEALTHCHECKS_CHECKS`        | [`PingRegistry`]                | Comma-separated `name=uuid-or-slug` pairs                          |
+//! | `HEALTHCHECKS_SPOOL_DIR`     | [`PingClient`]                  | Directory to [spool](crate::spool::Spool) undelivered pings in     |
+//! | `HEALTHCHECKS_RETRIES`       | [`PingClient`]                  | Number of times a ping is retried before giving up                 |
+//! | `HEALTHCHECKS_TOKEN`         | [`ManageClient`]                | API key of the project                                             |
+//! | `HEALTHCHECKS_API_URL`       | [`ManageClient`]                | Base URL of the management API, including the version              |
+//! | `HEALTHCHECKS_USERAGENT`     | [`PingClient`], [`ManageClient`]| User agent sent with every request                                 |
+//! | `HEALTHCHECKS_TIMEOUT`       | [`PingClient`], [`ManageClient`]| Timeout for every request, in seconds                              |
+//!
+//! [`PingClient`]: crate::ping::PingClient
+//! [`ManageClient`]: crate::manage::ManageClient
+//! [`PingRegistry`]: crate::registry::PingRegistry
+//! [`PingClient::from_env`]: crate::ping::PingClient::from_env
+//! [`ManageClient::from_env`]: crate::manage::ManageClient::from_env
+//! [`PingRegistry::from_env`]: crate::registry::PingRegistry::from_env
+use crate::errors::HealthchecksConfigError;
+use std::str::FromStr;
+
+/// Full ping URL of a check.
+pub const HEALTHCHECKS_PING_URL: &str = "HEALTHCHECKS_PING_URL";
+/// UUID or slug of a check.
+pub const HEALTHCHECKS_CHECK_ID: &str = "HEALTHCHECKS_CHECK_ID";
+/// Ping key of a project, used for checks identified by slug.
+pub const HEALTHCHECKS_PING_KEY: &str = "HEALTHCHECKS_PING_KEY";
+/// Base URL of the pinging API.
+pub const HEALTHCHECKS_PING_API_URL: &str = "HEALTHCHECKS_PING_API_URL";
+/// Comma-separated `name=uuid-or-slug` pairs for a [`PingRegistry`](crate::registry::PingRegistry).
+pub const HEALTHCHECKS_CHECKS: &str = "HEALTHCHECKS_CHECKS";
+/// Directory to spool undelivered pings in.
+pub const HEALTHCHECKS_SPOOL_DIR: &str = "HEALTHCHECKS_SPOOL_DIR";
+/// Number of times a ping is retried before giving up.
+pub const HEALTHCHECKS_RETRIES: &str = "HEALTHCHECKS_RETRIES";
+/// API key of a project.
+pub const HEALTHCHECKS_TOKEN: &str = "HEALTHCHECKS_TOKEN";
+/// Base URL of the management API.
+pub const HEALTHCHECKS_API_URL: &str = "HEALTHCHECKS_API_URL";
+/// User agent sent with every request.
+pub const HEALTHCHECKS_USERAGENT: &str = "HEALTHCHECKS_USERAGENT";
+/// Timeout for every request, in seconds.
+pub const HEALTHCHECKS_TIMEOUT: &str = "HEALTHCHECKS_TIMEOUT";
+
+/// Look up a variable in the environment of the process.
+pub(crate) fn var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+/// Read a variable with `lookup`, treating an empty value as missing.
+pub(crate) fn optional(lookup: impl Fn(&str) -> Option<String>, name: &str) -> Option<String> {
+    lookup(name).filter(|value| !value.is_empty())
+}
+
+/// Read a variable with `lookup` that must be set.
+pub(crate) fn required(
+    lookup: impl Fn(&str) -> Option<String>,
+    name: &'static str,
+) -> Result<String, HealthchecksConfigError> {
+    optional(lookup, name).ok_or(HealthchecksConfigError::MissingEnvVar(name))
+}
+
+/// Read and parse a variable with `lookup`, if it is set.
+pub(crate) fn parse<T: FromStr>(
+    lookup: impl Fn(&str) -> Option<String>,
+    name: &'static str,
+) -> Result<Option<T>, HealthchecksConfigError> {
+    optional(lookup, name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| HealthchecksConfigError::InvalidEnvVar { name, value })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HEALTHCHECKS_TEST_INVALID" => Some("five".to_owned()),
+            "HEALTHCHECKS_TEST_EMPTY" => Some(String::new()),
+            "HEALTHCHECKS_TEST_VALID" => Some("5".to_owned()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn missing_variable_is_named() {
+        let err = required(lookup, "HEALTHCHECKS_TEST_MISSING").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "environment variable HEALTHCHECKS_TEST_MISSING must be set"
+        );
+    }
+
+    #[test]
+    fn invalid_number_is_rejected() {
+        let result = parse::<u64>(lookup, "HEALTHCHECKS_TEST_INVALID");
+        assert!(matches!(
+            result,
+            Err(HealthchecksConfigError::InvalidEnvVar { name: "HEALTHCHECKS_TEST_INVALID", ref value }) if value == "five"
+        ));
+    }
+
+    #[test]
+    fn empty_variable_is_missing() {
+        assert_eq!(
+            parse::<u64>(lookup, "HEALTHCHECKS_TEST_EMPTY").unwrap(),
+            None
+        );
+        assert_eq!(
+            parse::<u64>(lookup, "HEALTHCHECKS_TEST_VALID").unwrap(),
+            Some(5)
+        );
+    }
+}
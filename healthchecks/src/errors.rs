@@ -22,6 +22,9 @@ pub enum HealthchecksApiError {
     /// The request body was invalid. This shouldn't ever be hit by users, but this is just in case
     #[error("the request is not well-formed, violates schema, or uses invalid field values")]
     NotWellFormed,
+    /// The check was rejected locally before being sent to the server
+    #[error("invalid check: {0}")]
+    InvalidCheck(#[from] HealthchecksValidationError),
     /// The check limit for the account was reached
     #[error("the account's check limit has been reached")]
     CheckLimitReached,
@@ -60,4 +63,130 @@ pub enum HealthchecksConfigError {
     /// Invalid UUID
     #[error("invalid UUID: {0}")]
     InvalidUuid(String),
+    /// The ping URL is not a valid UUID or slug ping URL
+    #[error("invalid ping URL: {0}")]
+    InvalidPingUrl(String),
+    /// A check was identified by its slug, but no ping key was provided
+    #[error("check '{0}' is identified by a slug, which requires a ping key")]
+    MissingPingKey(String),
+    /// No check with the given name is known to the [`PingRegistry`](crate::registry::PingRegistry)
+    #[error("no check named '{name}' in registry, known checks: {known}")]
+    UnknownCheck {
+        /// The name that was looked up
+        name: String,
+        /// Comma-separated list of the names the registry knows about
+        known: String,
+    },
+    /// A required environment variable is not set
+    #[error("environment variable {0} must be set")]
+    MissingEnvVar(&'static str),
+    /// An environment variable is set to a value that could not be parsed
+    #[error("invalid value for environment variable {name}: {value}")]
+    InvalidEnvVar {
+        /// Name of the environment variable
+        name: &'static str,
+        /// The value that could not be parsed
+        value: String,
+    },
+    /// The configuration for a [`PingRegistry`](crate::registry::PingRegistry) could not be parsed
+    #[error("invalid registry configuration: {0}")]
+    InvalidRegistry(String),
+}
+
+/// Errors raised when modelling the state of a [`Check`](crate::model::Check) locally
+#[derive(Debug, Error)]
+pub enum HealthchecksStateError {
+    /// The check has neither a timeout nor a schedule
+    #[error("check has neither a timeout nor a schedule")]
+    MissingPeriod,
+    /// The schedule or timezone of the check is invalid
+    #[error(transparent)]
+    InvalidSchedule(#[from] HealthchecksValidationError),
+    /// The status of the check is not one known to healthchecks
+    #[error("unknown check status: {0}")]
+    UnknownStatus(String),
+    /// A timestamp returned by the API could not be parsed
+    #[error("invalid timestamp: {0}")]
+    InvalidTimestamp(String),
+}
+
+/// Errors raised when validating a [`NewCheck`](crate::model::NewCheck) or
+/// [`UpdatedCheck`](crate::model::UpdatedCheck) locally
+#[derive(Debug, Error)]
+pub enum HealthchecksValidationError {
+    /// The cron expression could not be parsed or never matches
+    #[error("invalid schedule '{expression}': {reason}")]
+    InvalidSchedule {
+        /// The cron expression that was rejected
+        expression: String,
+        /// Why the expression was rejected
+        reason: String,
+    },
+    /// The timezone is not a known IANA timezone
+    #[error("unknown timezone: {0}")]
+    InvalidTimezone(String),
+    /// A numeric field is outside of the range accepted by the API
+    #[error("{field} must be between {min} and {max}, found {value}")]
+    OutOfRange {
+        /// Name of the field
+        field: &'static str,
+        /// The rejected value
+        value: i64,
+        /// Smallest accepted value
+        min: i64,
+        /// Largest accepted value
+        max: i64,
+    },
+    /// A text field is longer than the API accepts
+    #[error("{field} must be at most {max} characters long")]
+    TooLong {
+        /// Name of the field
+        field: &'static str,
+        /// Largest accepted length
+        max: usize,
+    },
+    /// A tag contains whitespace, which separates tags in the API
+    #[error("tags must not contain whitespace: '{0}'")]
+    InvalidTag(String),
+    /// A field listed in `unique` is not one the API supports
+    #[error("unsupported unique field: {0}")]
+    InvalidUniqueField(String),
+    /// Both a timeout and a schedule were set on the same check
+    #[error("a check can have either a timeout or a schedule, not both")]
+    TimeoutAndSchedule,
+}
+
+/// Errors raised when planning or applying changes to reach a set of
+/// [`DesiredChecks`](crate::plan::DesiredChecks)
+#[derive(Debug, Error)]
+pub enum HealthchecksPlanError {
+    /// The desired checks could not be read or parsed
+    #[error("invalid check configuration: {0}")]
+    InvalidConfig(String),
+    /// Several desired checks have the same name or match the same existing check,
+    /// or a desired check is matched by name and several existing checks have that name
+    #[error("check '{0}' is configured more than once, or several checks have that name")]
+    DuplicateCheck(String),
+    /// A desired check uses a channel that does not exist in the project
+    #[error("check '{check}' uses unknown channel '{channel}'")]
+    UnknownChannel {
+        /// Name of the check
+        check: String,
+        /// The channel that was not found
+        channel: String,
+    },
+    /// An existing check has no UUID, which happens with read-only API keys
+    #[error("check '{0}' has no UUID, make sure you're not using a read-only key")]
+    MissingId(String),
+    /// A desired check failed validation
+    #[error("invalid check '{name}': {source}")]
+    InvalidCheck {
+        /// Name of the check
+        name: String,
+        /// Why the check is invalid
+        source: HealthchecksValidationError,
+    },
+    /// Fetching or changing checks failed
+    #[error(transparent)]
+    Api(#[from] HealthchecksApiError),
 }
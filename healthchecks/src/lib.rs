@@ -1,4 +1,8 @@
 #![doc = include_str!("../README.md")]
+/// Non-blocking delivery of pings from a background thread.
+pub mod background;
+/// Environment variables read by the `from_env` constructors.
+pub mod env;
 /// Error types for public API
 pub mod errors;
 /// Functions for interacting with the Healthchecks management API.
@@ -7,6 +11,16 @@ pub mod manage;
 pub mod model;
 /// Functions for interacting with the Healthchecks pinging API.
 pub mod ping;
+/// Declarative management of checks by planning and applying changes.
+pub mod plan;
+/// Named collection of ping clients for several checks.
+pub mod registry;
+/// Cron schedules of checks and the times they are expected to ping.
+pub mod schedule;
+/// On-disk queue for pings that could not be delivered.
+pub mod spool;
+/// Uptime and run duration statistics of checks.
+pub mod stats;
 /// The default User-Agent header value for the library
 pub(crate) const DEFAULT_USER_AGENT: &str =
     concat!("healthchecks-rs", "/", env!("CARGO_PKG_VERSION"));
@@ -1,10 +1,15 @@
 use crate::{
+    env::{
+        self, HEALTHCHECKS_API_URL, HEALTHCHECKS_TIMEOUT, HEALTHCHECKS_TOKEN,
+        HEALTHCHECKS_USERAGENT,
+    },
     errors::{HealthchecksApiError, HealthchecksConfigError},
     model::{Channel, Check, Flip, NewCheck, Ping, UpdatedCheck},
     DEFAULT_USER_AGENT,
 };
 use std::result::Result;
-use ureq::{delete, get, post, Error, Request};
+use std::time::Duration;
+use ureq::{Agent, AgentBuilder, Error, Request};
 
 const HEALTHCHECK_API_URL: &str = if cfg!(feature = "v3") {
     "https://healthchecks.io/api/v3"
@@ -24,6 +29,7 @@ pub struct ManageClient {
     pub(crate) api_key: String,
     pub(crate) user_agent: String,
     pub(crate) api_url: String,
+    pub(crate) ureq_agent: Agent,
 }
 
 /// Create an instance of [`ManageClient`] from a given API key and an
@@ -71,6 +77,7 @@ pub fn get_client_with_url(
             api_key,
             user_agent,
             api_url,
+            ureq_agent: AgentBuilder::new().build(),
         })
     }
 }
@@ -85,20 +92,50 @@ pub enum UpsertResult {
 }
 
 impl ManageClient {
+    /// Create a client configured from the environment variables documented in
+    /// [`env`](crate::env): `HEALTHCHECKS_TOKEN` for the API key, and optionally
+    /// `HEALTHCHECKS_API_URL`, `HEALTHCHECKS_USERAGENT` and `HEALTHCHECKS_TIMEOUT`.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksConfigError::MissingEnvVar`] if `HEALTHCHECKS_TOKEN` is not set.
+    /// - Returns [`HealthchecksConfigError::InvalidEnvVar`] if `HEALTHCHECKS_TIMEOUT` is not a number.
+    pub fn from_env() -> Result<ManageClient, HealthchecksConfigError> {
+        let mut client = get_client_with_url(
+            env::required(env::var, HEALTHCHECKS_TOKEN)?,
+            env::optional(env::var, HEALTHCHECKS_USERAGENT),
+            env::optional(env::var, HEALTHCHECKS_API_URL)
+                .unwrap_or_else(|| HEALTHCHECK_API_URL.to_owned()),
+        )?;
+        if let Some(timeout) = env::parse(env::var, HEALTHCHECKS_TIMEOUT)? {
+            client = client.set_timeout(Duration::from_secs(timeout));
+        }
+        Ok(client)
+    }
+
+    /// Set the timeout for every request made by this client. No timeout is set by default.
+    #[must_use]
+    pub fn set_timeout(mut self, timeout: Duration) -> ManageClient {
+        self.ureq_agent = AgentBuilder::new().timeout(timeout).build();
+        self
+    }
+
     fn ureq_get(&self, path: &str) -> Request {
-        get(path)
+        self.ureq_agent
+            .get(path)
             .set("X-Api-Key", &self.api_key)
             .set("User-Agent", &self.user_agent)
     }
 
     fn ureq_post(&self, path: &str) -> Request {
-        post(path)
+        self.ureq_agent
+            .post(path)
             .set("X-Api-Key", &self.api_key)
             .set("User-Agent", &self.user_agent)
     }
 
     fn ureq_delete(&self, path: &str) -> Request {
-        delete(path)
+        self.ureq_agent
+            .delete(path)
             .set("X-Api-Key", &self.api_key)
             .set("User-Agent", &self.user_agent)
     }
@@ -215,8 +252,8 @@ impl ManageClient {
         struct PingsResult {
             pub pings: Vec<Ping>,
         }
-        let r = self.ureq_post(&format!("{}/checks/{}/pings", self.api_url, check_id));
-        match r.send_string("") {
+        let r = self.ureq_get(&format!("{}/checks/{}/pings/", self.api_url, check_id));
+        match r.call() {
             Ok(response) => Ok(response.into_json::<PingsResult>()?.pings),
             Err(Error::Status(401, _)) => Err(HealthchecksApiError::InvalidApiKey),
             Err(Error::Status(403, _)) => Err(HealthchecksApiError::AccessDenied),
@@ -230,6 +267,37 @@ impl ManageClient {
         }
     }
 
+    /// Get the body sent with the ping number `n` of the check with the given
+    /// UUID or unique key, as listed in [`Ping::body_url`].
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksApiError::InvalidApiKey`] if the API key is invalid
+    /// - Returns [`HealthchecksApiError::TransportError`] if there was a network problem
+    ///   preventing the API request from completing.
+    /// - Returns [`HealthchecksApiError::UnexpectedError`] if the healthchecks server responded unexpectedly.
+    /// - Returns [`HealthchecksApiError::AccessDenied`] if the API key does not have access to the `check_id`.
+    /// - Returns [`HealthchecksApiError::NoCheckFound`] if no check was found for the given `check_id`,
+    ///   or the ping does not exist or has no body.
+    #[cfg(feature = "v3")]
+    pub fn get_ping_body(&self, check_id: &str, n: i64) -> ApiResult<String> {
+        let r = self.ureq_get(&format!(
+            "{}/checks/{}/pings/{}/body",
+            self.api_url, check_id, n
+        ));
+        match r.call() {
+            Ok(response) => Ok(response.into_string()?),
+            Err(Error::Status(401, _)) => Err(HealthchecksApiError::InvalidApiKey),
+            Err(Error::Status(403, _)) => Err(HealthchecksApiError::AccessDenied),
+            Err(Error::Status(404, _)) => {
+                Err(HealthchecksApiError::NoCheckFound(check_id.to_string()))
+            }
+            Err(Error::Status(_, response)) => Err(HealthchecksApiError::UnexpectedError(
+                response.into_string()?,
+            )),
+            Err(Error::Transport(err)) => Err(HealthchecksApiError::TransportError(Box::new(err))),
+        }
+    }
+
     /// Get a list of check's status changes with the given UUID or unique key.
     ///
     /// # Errors
@@ -240,9 +308,13 @@ impl ManageClient {
     /// - Returns [`HealthchecksApiError::AccessDenied`] if the API key does not have access to the `check_id`.
     /// - Returns [`HealthchecksApiError::NoCheckFound`] if no check was found for the given `check_id`.
     pub fn list_status_changes(&self, check_id: &str) -> ApiResult<Vec<Flip>> {
-        let r = self.ureq_post(&format!("{}/checks/{}/flips", self.api_url, check_id));
+        #[derive(serde_derive::Deserialize)]
+        struct FlipsResult {
+            pub flips: Vec<Flip>,
+        }
+        let r = self.ureq_get(&format!("{}/checks/{}/flips/", self.api_url, check_id));
         match r.call() {
-            Ok(response) => Ok(response.into_json::<Vec<Flip>>()?),
+            Ok(response) => Ok(response.into_json::<FlipsResult>()?.flips),
             Err(Error::Status(401, _)) => Err(HealthchecksApiError::InvalidApiKey),
             Err(Error::Status(403, _)) => Err(HealthchecksApiError::AccessDenied),
             Err(Error::Status(404, _)) => {
@@ -291,6 +363,7 @@ impl ManageClient {
     /// - Returns [`HealthchecksApiError::NoCheckFound`] if no check was found for the given `check_id`.
     /// - Returns [`HealthchecksApiError::NotWellFormed`] if the request body was malformed. This should never happen in practice,
     ///   please report it on GitHub if you encounter an error of this type.
+    /// - Returns [`HealthchecksApiError::InvalidCheck`] if the schedule or timezone of the check is invalid.
     /// - Returns [`HealthchecksApiError::ExistingCheckMatched`] if the check already exists.
     pub fn create_check(&self, check: NewCheck) -> ApiResult<Check> {
         self.upsert_check(check).and_then(|(result, check)| {
@@ -318,7 +391,9 @@ impl ManageClient {
     /// - Returns [`HealthchecksApiError::NoCheckFound`] if no check was found for the given `check_id`.
     /// - Returns [`HealthchecksApiError::NotWellFormed`] if the request body was malformed. This should never happen in practice,
     ///   please report it on GitHub if you encounter an error of this type.
+    /// - Returns [`HealthchecksApiError::InvalidCheck`] if the schedule or timezone of the check is invalid.
     pub fn upsert_check(&self, check: NewCheck) -> ApiResult<(UpsertResult, Check)> {
+        check.validate()?;
         let check_json = serde_json::to_value(check)?;
         let r = self.ureq_post(&format!("{}/{}/", self.api_url, "checks"));
         match r
@@ -354,7 +429,9 @@ impl ManageClient {
     /// - Returns [`HealthchecksApiError::NoCheckFound`] if no check was found for the given `check_id`.
     /// - Returns [`HealthchecksApiError::NotWellFormed`] if the request body was malformed. This should never happen in practice,
     ///   please report it on GitHub if you encounter an error of this type.
+    /// - Returns [`HealthchecksApiError::InvalidCheck`] if the schedule or timezone of the check is invalid.
     pub fn update_check(&self, check: UpdatedCheck, check_id: &str) -> ApiResult<Check> {
+        check.validate()?;
         let check_json = serde_json::to_value(check)?;
         let r = self.ureq_post(&format!("{}/{}/{}", self.api_url, "checks", check_id));
         match r
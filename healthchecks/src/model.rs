@@ -1,5 +1,20 @@
+use crate::errors::HealthchecksValidationError;
+use crate::schedule;
+pub use builder::{NewCheckBuilder, UpdatedCheckBuilder};
 use serde_derive::{Deserialize, Serialize};
 
+/// Builders for [`NewCheck`] and [`UpdatedCheck`].
+pub mod builder;
+/// Local computation of check statuses from the pings they receive.
+pub mod state;
+
+/// Smallest and largest `timeout` and `grace` accepted by the API, in seconds.
+pub const PERIOD_BOUNDS: (i32, i32) = (60, 31_536_000);
+/// Longest check name accepted by the API.
+pub const MAX_NAME_LENGTH: usize = 100;
+/// Longest cron expression accepted by the API.
+pub const MAX_SCHEDULE_LENGTH: usize = 100;
+
 /// This struct encapsulates a check as represented in the healthchecks.io
 /// API. Fields marked optional are either optional in the default API response
 /// or can be present or missing if a read-only API key is used.
@@ -89,6 +104,32 @@ impl Check {
             None
         }
     }
+
+    /// IDs of the integration channels associated with this check, parsed from
+    /// [`Check::channels`]. Empty when no integrations are configured, or when
+    /// the API did not report them, see [`Check::channel_list`].
+    #[must_use]
+    pub fn channel_ids(&self) -> Vec<String> {
+        self.channels
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(ToOwned::to_owned)
+            .collect()
+    }
+
+    /// The integration channels associated with this check, in the form taken
+    /// by [`NewCheckBuilder::channels`] and [`UpdatedCheckBuilder::channels`].
+    /// Is [`None`] when the API did not report them, such as for read-only API
+    /// keys, so they are never mistaken for [`Channels::None`].
+    #[must_use]
+    pub fn channel_list(&self) -> Option<Channels> {
+        self.channels
+            .as_ref()
+            .map(|_| Channels::from_ids(self.channel_ids()))
+    }
 }
 
 /// Represents an integration, like email or sms.
@@ -106,11 +147,17 @@ pub struct Channel {
 /// using the admin API. It skips over many fields from the [`Check`]
 /// struct that are generated server-side by healthchecks.
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct NewCheck {
     /// Name of the check.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
+    /// Slug of the check, derived from the name by the server when unset.
+    #[cfg(feature = "v3")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+
     /// Space separated list of tags set on this check.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<String>,
@@ -123,7 +170,7 @@ pub struct NewCheck {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout: Option<i32>,
 
-    /// When a check is late, how long to wait until an alert is sent. Value in minutes, is [`None`] when no timeout is set.
+    /// When a check is late, how long to wait until an alert is sent. Value in seconds, is [`None`] when no grace period is set.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub grace: Option<i32>,
 
@@ -138,7 +185,7 @@ pub struct NewCheck {
     /// Indicates if the ping has been manually paused and will not resume automatically
     /// on a new ping. These checks need to manually be resumed from the web dashboard.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub manual_resume: Option<String>,
+    pub manual_resume: Option<bool>,
 
     /// Comma-separated list of IDs of the integration channels associated with this check. Is [`None`] when no integrations
     /// are configured.
@@ -150,15 +197,52 @@ pub struct NewCheck {
     pub unique: Option<Vec<String>>,
 }
 
+impl NewCheck {
+    /// Start building a [`NewCheck`] with typed fields.
+    #[must_use]
+    pub fn builder() -> NewCheckBuilder {
+        NewCheckBuilder::default()
+    }
+
+    /// Validate the fields of this check the same way the server would, to
+    /// catch mistakes before sending it.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksValidationError::OutOfRange`] if the timeout or grace period is out of bounds.
+    /// - Returns [`HealthchecksValidationError::TooLong`] if the name or schedule is too long.
+    /// - Returns [`HealthchecksValidationError::InvalidSchedule`] if the schedule is not a valid cron expression.
+    /// - Returns [`HealthchecksValidationError::InvalidTimezone`] if the timezone is not a known IANA timezone.
+    /// - Returns [`HealthchecksValidationError::InvalidUniqueField`] if `unique` has an unknown field.
+    pub fn validate(&self) -> Result<(), HealthchecksValidationError> {
+        validate_fields(
+            self.name.as_deref(),
+            self.timeout,
+            self.grace,
+            self.schedule.as_deref(),
+            self.tz.as_deref(),
+        )?;
+        for field in self.unique.iter().flatten() {
+            field.parse::<UniqueField>()?;
+        }
+        Ok(())
+    }
+}
+
 /// Represents an existing check which needs some or all of its values updated
 /// on the healthchecks server. Fields that do not need updates should be set to
 /// [`None`](std::option::Option::None) and will be skipped from serialization.
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct UpdatedCheck {
     /// Name of the check.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
+    /// Slug of the check, derived from the name by the server when unset.
+    #[cfg(feature = "v3")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+
     /// Space separated list of tags set on this check.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<String>,
@@ -171,7 +255,7 @@ pub struct UpdatedCheck {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout: Option<i32>,
 
-    /// When a check is late, how long to wait until an alert is sent. Value in minutes, is [`None`] when no timeout is set.
+    /// When a check is late, how long to wait until an alert is sent. Value in seconds, is [`None`] when no grace period is set.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub grace: Option<i32>,
 
@@ -186,7 +270,7 @@ pub struct UpdatedCheck {
     /// Indicates if the ping has been manually paused and will not resume automatically
     /// on a new ping. These checks need to manually be resumed from the web dashboard.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub manual_resume: Option<String>,
+    pub manual_resume: Option<bool>,
 
     /// Comma-separated list of IDs of the integration channels associated with this check. Is [`None`] when no integrations
     /// are configured.
@@ -194,6 +278,176 @@ pub struct UpdatedCheck {
     pub channels: Option<String>,
 }
 
+impl UpdatedCheck {
+    /// Start building an [`UpdatedCheck`] with typed fields.
+    #[must_use]
+    pub fn builder() -> UpdatedCheckBuilder {
+        UpdatedCheckBuilder::default()
+    }
+
+    /// Validate the fields of this check the same way the server would, to
+    /// catch mistakes before sending it.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksValidationError::OutOfRange`] if the timeout or grace period is out of bounds.
+    /// - Returns [`HealthchecksValidationError::TooLong`] if the name or schedule is too long.
+    /// - Returns [`HealthchecksValidationError::InvalidSchedule`] if the schedule is not a valid cron expression.
+    /// - Returns [`HealthchecksValidationError::InvalidTimezone`] if the timezone is not a known IANA timezone.
+    pub fn validate(&self) -> Result<(), HealthchecksValidationError> {
+        validate_fields(
+            self.name.as_deref(),
+            self.timeout,
+            self.grace,
+            self.schedule.as_deref(),
+            self.tz.as_deref(),
+        )
+    }
+}
+
+/// Integration channels to assign to a check. Deserializes from either the
+/// API's comma separated string, where `*` stands for all channels, or a list.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "ChannelsRepr", into = "ChannelsRepr")]
+pub enum Channels {
+    /// Every channel in the project.
+    All,
+    /// No channels.
+    None,
+    /// The channels with the given IDs or names.
+    List(Vec<String>),
+}
+
+impl Channels {
+    /// The channels with the given IDs or names, or [`Channels::None`] if there
+    /// are none.
+    #[must_use]
+    pub fn from_ids(ids: Vec<String>) -> Channels {
+        if ids.is_empty() {
+            Channels::None
+        } else {
+            Channels::List(ids)
+        }
+    }
+
+    /// Value of the `channels` field sent to the API.
+    #[must_use]
+    pub fn to_api_string(&self) -> String {
+        match self {
+            Channels::All => "*".to_owned(),
+            Channels::None => String::new(),
+            Channels::List(channels) => channels.join(","),
+        }
+    }
+}
+
+/// Serialized form of [`Channels`].
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+enum ChannelsRepr {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl From<ChannelsRepr> for Channels {
+    fn from(repr: ChannelsRepr) -> Channels {
+        match repr {
+            ChannelsRepr::Text(text) => match text.trim() {
+                "*" => Channels::All,
+                "" => Channels::None,
+                text => Channels::List(text.split(',').map(|c| c.trim().to_owned()).collect()),
+            },
+            ChannelsRepr::List(list) if list.is_empty() => Channels::None,
+            ChannelsRepr::List(list) => Channels::List(list),
+        }
+    }
+}
+
+impl From<Channels> for ChannelsRepr {
+    fn from(channels: Channels) -> ChannelsRepr {
+        match channels {
+            Channels::List(list) => ChannelsRepr::List(list),
+            channels => ChannelsRepr::Text(channels.to_api_string()),
+        }
+    }
+}
+
+/// Fields that can be required to be unique when creating a check, in which
+/// case an existing check with the same values is returned instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UniqueField {
+    /// The name of the check.
+    Name,
+    /// The tags of the check.
+    Tags,
+    /// The timeout of the check.
+    Timeout,
+    /// The grace period of the check.
+    Grace,
+    /// The slug of the check.
+    Slug,
+}
+
+impl UniqueField {
+    /// Name of the field used by the API.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UniqueField::Name => "name",
+            UniqueField::Tags => "tags",
+            UniqueField::Timeout => "timeout",
+            UniqueField::Grace => "grace",
+            UniqueField::Slug => "slug",
+        }
+    }
+}
+
+impl std::str::FromStr for UniqueField {
+    type Err = HealthchecksValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(UniqueField::Name),
+            "tags" => Ok(UniqueField::Tags),
+            "timeout" => Ok(UniqueField::Timeout),
+            "grace" => Ok(UniqueField::Grace),
+            "slug" => Ok(UniqueField::Slug),
+            _ => Err(HealthchecksValidationError::InvalidUniqueField(
+                s.to_owned(),
+            )),
+        }
+    }
+}
+
+/// Validate the fields shared by [`NewCheck`] and [`UpdatedCheck`].
+fn validate_fields(
+    name: Option<&str>,
+    timeout: Option<i32>,
+    grace: Option<i32>,
+    schedule: Option<&str>,
+    tz: Option<&str>,
+) -> Result<(), HealthchecksValidationError> {
+    let (min, max) = PERIOD_BOUNDS;
+    for (field, value) in [("timeout", timeout), ("grace", grace)] {
+        if let Some(value) = value.filter(|value| !(min..=max).contains(value)) {
+            return Err(HealthchecksValidationError::OutOfRange {
+                field,
+                value: i64::from(value),
+                min: i64::from(min),
+                max: i64::from(max),
+            });
+        }
+    }
+    for (field, value, max) in [
+        ("name", name, MAX_NAME_LENGTH),
+        ("schedule", schedule, MAX_SCHEDULE_LENGTH),
+    ] {
+        if value.is_some_and(|value| value.chars().count() > max) {
+            return Err(HealthchecksValidationError::TooLong { field, max });
+        }
+    }
+    schedule::validate(schedule, tz)
+}
+
 /// Represents a ping that a check has received.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Ping {
@@ -227,11 +481,27 @@ pub struct Ping {
     #[serde(skip_serializing_if = "Option::is_none")]
     /// Duration for which this ping ran, Will be [None] for untimed pings.
     pub duration: Option<f64>,
+
+    /// Run ID sent with the ping, which pairs a start ping with the ping that finished the run.
+    #[cfg(feature = "v3")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rid: Option<String>,
+
+    /// Exit status reported with the ping, when it was sent with one.
+    #[cfg(feature = "v3")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exitstatus: Option<i32>,
+
+    /// API URL of the body sent with the ping, when it was sent with one. The
+    /// body can be fetched with [`ManageClient::get_ping_body`](crate::manage::ManageClient::get_ping_body).
+    #[cfg(feature = "v3")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_url: Option<String>,
 }
 
 /// Represents a "flip" in state this check has experienced. This event
 /// is generated when a check transitions between the up and down state.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Flip {
     /// RFC3339 timestamp for when the change occured
     pub timestamp: String,
@@ -261,6 +531,36 @@ mod tests {
         assert_eq!(value.unwrap().to_string(), "{}");
     }
 
+    #[test]
+    fn parses_channel_ids() {
+        let check = |channels: Option<&str>| -> Check {
+            serde_json::from_value(serde_json::json!({
+                "name": "backup",
+                "tags": "",
+                "desc": "",
+                "grace": 3600,
+                "n_pings": 0,
+                "status": "new",
+                "started": false,
+                "slug": "backup",
+                "manual_resume": false,
+                "channels": channels,
+            }))
+            .unwrap()
+        };
+        assert_eq!(
+            check(Some("4ec5a071-2d08-4baa-898a-eb4eb3cd6941, 746a083e")).channel_ids(),
+            ["4ec5a071-2d08-4baa-898a-eb4eb3cd6941", "746a083e"]
+        );
+        assert!(check(Some("")).channel_ids().is_empty());
+        assert_eq!(check(None).channel_list(), None);
+        assert_eq!(check(Some("")).channel_list(), Some(Channels::None));
+        assert_eq!(
+            check(Some("746a083e")).channel_list(),
+            Some(Channels::List(vec!["746a083e".to_owned()]))
+        );
+    }
+
     #[test]
     fn default_impl_for_updated_check_fills_nulls() {
         let updated_check = UpdatedCheck {
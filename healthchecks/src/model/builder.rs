@@ -0,0 +1,356 @@
+use crate::errors::HealthchecksValidationError;
+use crate::model::{Channels, NewCheck, UniqueField, UpdatedCheck};
+use std::time::Duration;
+
+/// Builder for a [`NewCheck`] that takes typed values for its fields and
+/// validates them against the bounds of the API.
+///
+/// ```rust
+/// # use healthchecks::model::{Channels, NewCheck, UniqueField};
+/// # use std::time::Duration;
+/// let check = NewCheck::builder()
+///     .name("nightly-backup")
+///     .tags(["prod", "db"])
+///     .timeout(Duration::from_secs(24 * 60 * 60))
+///     .grace(Duration::from_secs(30 * 60))
+///     .channels(Channels::All)
+///     .unique([UniqueField::Name])
+///     .build()
+///     .unwrap();
+/// assert_eq!(check.tags.as_deref(), Some("prod db"));
+/// assert_eq!(check.timeout, Some(86400));
+/// assert_eq!(check.channels.as_deref(), Some("*"));
+///
+/// assert!(NewCheck::builder().timeout(Duration::from_secs(5)).build().is_err());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct NewCheckBuilder {
+    check: NewCheck,
+    tags: Vec<String>,
+    timeout: Option<Duration>,
+    grace: Option<Duration>,
+}
+
+impl NewCheckBuilder {
+    /// Set the name of the check.
+    #[must_use]
+    pub fn name(mut self, name: &str) -> NewCheckBuilder {
+        self.check.name = Some(name.to_owned());
+        self
+    }
+
+    /// Set the tags of the check.
+    #[must_use]
+    pub fn tags<I, S>(mut self, tags: I) -> NewCheckBuilder
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the slug of the check, instead of the one derived from its name.
+    #[cfg(feature = "v3")]
+    #[must_use]
+    pub fn slug(mut self, slug: &str) -> NewCheckBuilder {
+        self.check.slug = Some(slug.to_owned());
+        self
+    }
+
+    /// Set the description of the check.
+    #[must_use]
+    pub fn desc(mut self, desc: &str) -> NewCheckBuilder {
+        self.check.desc = Some(desc.to_owned());
+        self
+    }
+
+    /// Expect the check to ping at most `timeout` after its previous ping. Cannot be
+    /// combined with [`schedule`](NewCheckBuilder::schedule).
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> NewCheckBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set how long to wait after a missed ping before sending an alert.
+    #[must_use]
+    pub fn grace(mut self, grace: Duration) -> NewCheckBuilder {
+        self.grace = Some(grace);
+        self
+    }
+
+    /// Expect the check to ping whenever the cron `schedule` matches in the timezone
+    /// `tz`, which defaults to UTC. Cannot be combined with [`timeout`](NewCheckBuilder::timeout).
+    #[must_use]
+    pub fn schedule(mut self, schedule: &str, tz: Option<&str>) -> NewCheckBuilder {
+        self.check.schedule = Some(schedule.to_owned());
+        self.check.tz = tz.map(ToOwned::to_owned);
+        self
+    }
+
+    /// Keep the check paused when it receives pings, until it is resumed manually.
+    #[must_use]
+    pub fn manual_resume(mut self, manual_resume: bool) -> NewCheckBuilder {
+        self.check.manual_resume = Some(manual_resume);
+        self
+    }
+
+    /// Set the integration channels of the check.
+    #[must_use]
+    pub fn channels(mut self, channels: Channels) -> NewCheckBuilder {
+        self.check.channels = Some(channels.to_api_string());
+        self
+    }
+
+    /// Return an existing check instead of creating a new one if it has the same
+    /// values for all of `fields`.
+    #[must_use]
+    pub fn unique<I: IntoIterator<Item = UniqueField>>(mut self, fields: I) -> NewCheckBuilder {
+        let fields = fields.into_iter().map(|f| f.as_str().to_owned()).collect();
+        self.check.unique = Some(fields);
+        self
+    }
+
+    /// Validate the fields and build the [`NewCheck`].
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksValidationError::TimeoutAndSchedule`] if both a timeout and a schedule were set.
+    /// - Returns [`HealthchecksValidationError::InvalidTag`] if a tag contains whitespace.
+    /// - Returns any error from [`NewCheck::validate`].
+    pub fn build(self) -> Result<NewCheck, HealthchecksValidationError> {
+        let (timeout, schedule) = (self.timeout, &self.check.schedule);
+        let check = NewCheck {
+            tags: join_tags(self.tags)?,
+            timeout: period(timeout, schedule.as_deref())?,
+            grace: self.grace.map(seconds),
+            ..self.check
+        };
+        check.validate()?;
+        Ok(check)
+    }
+}
+
+/// Builder for an [`UpdatedCheck`] that takes typed values for its fields and
+/// validates them against the bounds of the API. Fields that are not set are
+/// left unchanged on the server.
+///
+/// ```rust
+/// # use healthchecks::model::{Channels, UpdatedCheck};
+/// let check = UpdatedCheck::builder()
+///     .schedule("0 3 * * *", Some("Europe/Riga"))
+///     .channels(Channels::List(vec!["Email".to_owned(), "Slack".to_owned()]))
+///     .manual_resume(true)
+///     .build()
+///     .unwrap();
+/// assert_eq!(check.channels.as_deref(), Some("Email,Slack"));
+/// assert_eq!(check.name, None);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct UpdatedCheckBuilder {
+    check: UpdatedCheck,
+    tags: Option<Vec<String>>,
+    timeout: Option<Duration>,
+    grace: Option<Duration>,
+}
+
+impl UpdatedCheckBuilder {
+    /// Set the name of the check.
+    #[must_use]
+    pub fn name(mut self, name: &str) -> UpdatedCheckBuilder {
+        self.check.name = Some(name.to_owned());
+        self
+    }
+
+    /// Set the slug of the check.
+    #[cfg(feature = "v3")]
+    #[must_use]
+    pub fn slug(mut self, slug: &str) -> UpdatedCheckBuilder {
+        self.check.slug = Some(slug.to_owned());
+        self
+    }
+
+    /// Replace the tags of the check.
+    #[must_use]
+    pub fn tags<I, S>(mut self, tags: I) -> UpdatedCheckBuilder
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags = Some(tags.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the description of the check.
+    #[must_use]
+    pub fn desc(mut self, desc: &str) -> UpdatedCheckBuilder {
+        self.check.desc = Some(desc.to_owned());
+        self
+    }
+
+    /// Expect the check to ping at most `timeout` after its previous ping, replacing
+    /// its schedule if it had one. Cannot be combined with
+    /// [`schedule`](UpdatedCheckBuilder::schedule).
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> UpdatedCheckBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set how long to wait after a missed ping before sending an alert.
+    #[must_use]
+    pub fn grace(mut self, grace: Duration) -> UpdatedCheckBuilder {
+        self.grace = Some(grace);
+        self
+    }
+
+    /// Expect the check to ping whenever the cron `schedule` matches in the timezone
+    /// `tz`, replacing its timeout if it had one. Cannot be combined with
+    /// [`timeout`](UpdatedCheckBuilder::timeout).
+    #[must_use]
+    pub fn schedule(mut self, schedule: &str, tz: Option<&str>) -> UpdatedCheckBuilder {
+        self.check.schedule = Some(schedule.to_owned());
+        self.check.tz = tz.map(ToOwned::to_owned);
+        self
+    }
+
+    /// Keep the check paused when it receives pings, until it is resumed manually.
+    #[must_use]
+    pub fn manual_resume(mut self, manual_resume: bool) -> UpdatedCheckBuilder {
+        self.check.manual_resume = Some(manual_resume);
+        self
+    }
+
+    /// Replace the integration channels of the check.
+    #[must_use]
+    pub fn channels(mut self, channels: Channels) -> UpdatedCheckBuilder {
+        self.check.channels = Some(channels.to_api_string());
+        self
+    }
+
+    /// Validate the fields and build the [`UpdatedCheck`].
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksValidationError::TimeoutAndSchedule`] if both a timeout and a schedule were set.
+    /// - Returns [`HealthchecksValidationError::InvalidTag`] if a tag contains whitespace.
+    /// - Returns any error from [`UpdatedCheck::validate`].
+    pub fn build(self) -> Result<UpdatedCheck, HealthchecksValidationError> {
+        let (timeout, schedule) = (self.timeout, &self.check.schedule);
+        let check = UpdatedCheck {
+            tags: match self.tags {
+                // An empty string clears the tags, rather than leaving them unchanged
+                Some(tags) => Some(join_tags(tags)?.unwrap_or_default()),
+                None => None,
+            },
+            timeout: period(timeout, schedule.as_deref())?,
+            grace: self.grace.map(seconds),
+            ..self.check
+        };
+        check.validate()?;
+        Ok(check)
+    }
+}
+
+/// Join tags into the space separated list used by the API.
+fn join_tags(tags: Vec<String>) -> Result<Option<String>, HealthchecksValidationError> {
+    if let Some(tag) = tags
+        .iter()
+        .find(|tag| tag.is_empty() || tag.contains(char::is_whitespace))
+    {
+        return Err(HealthchecksValidationError::InvalidTag(tag.clone()));
+    }
+    Ok((!tags.is_empty()).then(|| tags.join(" ")))
+}
+
+/// Convert the timeout to seconds, making sure it is not combined with a schedule.
+fn period(
+    timeout: Option<Duration>,
+    schedule: Option<&str>,
+) -> Result<Option<i32>, HealthchecksValidationError> {
+    match (timeout, schedule) {
+        (Some(_), Some(_)) => Err(HealthchecksValidationError::TimeoutAndSchedule),
+        (timeout, _) => Ok(timeout.map(seconds)),
+    }
+}
+
+fn seconds(duration: Duration) -> i32 {
+    i32::try_from(duration.as_secs()).unwrap_or(i32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_timeout_with_schedule() {
+        let result = NewCheck::builder()
+            .timeout(Duration::from_secs(3600))
+            .schedule("@daily", None)
+            .build();
+        assert!(matches!(
+            result,
+            Err(HealthchecksValidationError::TimeoutAndSchedule)
+        ));
+    }
+
+    #[test]
+    fn rejects_out_of_range_periods() {
+        let result = NewCheck::builder()
+            .grace(Duration::from_secs(366 * 24 * 60 * 60))
+            .build();
+        assert!(matches!(
+            result,
+            Err(HealthchecksValidationError::OutOfRange { field: "grace", .. })
+        ));
+        let result = UpdatedCheck::builder()
+            .timeout(Duration::from_secs(59))
+            .build();
+        assert!(matches!(
+            result,
+            Err(HealthchecksValidationError::OutOfRange {
+                field: "timeout",
+                value: 59,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_tags_and_names() {
+        assert!(matches!(
+            NewCheck::builder().tags(["prod db"]).build(),
+            Err(HealthchecksValidationError::InvalidTag(tag)) if tag == "prod db"
+        ));
+        assert!(matches!(
+            NewCheck::builder().name(&"a".repeat(101)).build(),
+            Err(HealthchecksValidationError::TooLong {
+                field: "name",
+                max: 100
+            })
+        ));
+    }
+
+    #[test]
+    fn serializes_typed_fields() {
+        let check = NewCheck::builder()
+            .tags(Vec::<String>::new())
+            .manual_resume(false)
+            .channels(Channels::None)
+            .unique([UniqueField::Name, UniqueField::Slug])
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_string(&check).unwrap(),
+            r#"{"manual_resume":false,"channels":"","unique":["name","slug"]}"#
+        );
+    }
+
+    #[test]
+    fn clears_tags_on_update() {
+        let check = UpdatedCheck::builder()
+            .tags(Vec::<String>::new())
+            .build()
+            .unwrap();
+        assert_eq!(check.tags.as_deref(), Some(""));
+    }
+}
@@ -0,0 +1,517 @@
+use crate::errors::HealthchecksStateError;
+use crate::model::{Check, Flip};
+use crate::ping::PingKind;
+use crate::schedule::{Schedule, DEFAULT_TIMEZONE};
+use jiff::{SignedDuration, Timestamp};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Status of a check, as shown in the healthchecks.io dashboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Status {
+    /// The check has not received any pings yet.
+    New,
+    /// The check received a success ping within its period.
+    Up,
+    /// The check missed its period and is within its grace time.
+    Grace,
+    /// The check received a failure ping, or missed its period and grace time.
+    Down,
+    /// The check received a start ping and is waiting for the job to finish.
+    Started,
+    /// Monitoring for the check has been paused.
+    Paused,
+}
+
+impl Status {
+    /// Name of the status used by the healthchecks.io API.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Status::New => "new",
+            Status::Up => "up",
+            Status::Grace => "grace",
+            Status::Down => "down",
+            Status::Started => "started",
+            Status::Paused => "paused",
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Status {
+    type Err = HealthchecksStateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "new" => Ok(Status::New),
+            "up" => Ok(Status::Up),
+            "grace" => Ok(Status::Grace),
+            "down" => Ok(Status::Down),
+            "started" => Ok(Status::Started),
+            "paused" => Ok(Status::Paused),
+            _ => Err(HealthchecksStateError::UnknownStatus(s.to_owned())),
+        }
+    }
+}
+
+/// How often a check is expected to ping.
+#[derive(Clone, Debug)]
+pub enum Period {
+    /// The check is expected to ping at most `timeout` after its previous ping.
+    Simple {
+        /// Expected time between pings.
+        timeout: Duration,
+    },
+    /// The check is expected to ping at every time its cron schedule matches.
+    Cron {
+        /// Schedule of the expected pings.
+        schedule: Schedule,
+    },
+}
+
+/// Local model of a check that follows the same rules as the healthchecks.io
+/// server to compute its status from the pings it receives, without polling
+/// the API.
+///
+/// ```rust
+/// # use healthchecks::model::state::{CheckState, Period, Status};
+/// # use healthchecks::ping::PingKind;
+/// # use std::time::Duration;
+/// let start: jiff::Timestamp = "2024-01-01T00:00:00Z".parse().unwrap();
+/// let mut state = CheckState::new(
+///     Period::Simple { timeout: Duration::from_secs(3600) },
+///     Duration::from_secs(600),
+/// );
+/// state.ping(PingKind::Success, None, start);
+/// assert_eq!(state.status(start), Status::Up);
+/// assert_eq!(state.status(start + jiff::SignedDuration::from_mins(65)), Status::Grace);
+/// assert_eq!(state.status(start + jiff::SignedDuration::from_mins(75)), Status::Down);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CheckState {
+    period: Period,
+    grace: SignedDuration,
+    manual_resume: bool,
+    /// Stored status, which is only ever one of new, up, down or paused. The
+    /// other statuses are derived from the current time.
+    status: Status,
+    last_ping: Option<Timestamp>,
+    last_start: Option<Timestamp>,
+    last_start_rid: Option<String>,
+    flips: Vec<Flip>,
+}
+
+impl CheckState {
+    /// Create the state of a new check that has not received any pings.
+    #[must_use]
+    pub fn new(period: Period, grace: Duration) -> CheckState {
+        CheckState {
+            period,
+            grace: to_signed(grace),
+            manual_resume: false,
+            status: Status::New,
+            last_ping: None,
+            last_start: None,
+            last_start_rid: None,
+            flips: Vec::new(),
+        }
+    }
+
+    /// Create the state of an existing check as returned by the management API.
+    ///
+    /// The API does not expose when a running job started, so a check reported
+    /// as started is modelled from its last success or failure ping instead.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksStateError::InvalidSchedule`] if the schedule or timezone of the check is invalid.
+    /// - Returns [`HealthchecksStateError::MissingPeriod`] if the check has neither a timeout nor a schedule.
+    /// - Returns [`HealthchecksStateError::UnknownStatus`] if the status of the check is not recognized.
+    /// - Returns [`HealthchecksStateError::InvalidTimestamp`] if the last ping could not be parsed.
+    pub fn from_check(check: &Check) -> Result<CheckState, HealthchecksStateError> {
+        let period = match (check.timeout, &check.schedule) {
+            (_, Some(schedule)) => Period::Cron {
+                schedule: Schedule::new(schedule, check.tz.as_deref().unwrap_or(DEFAULT_TIMEZONE))?,
+            },
+            (Some(timeout), None) => Period::Simple {
+                timeout: seconds(timeout),
+            },
+            (None, None) => return Err(HealthchecksStateError::MissingPeriod),
+        };
+        let last_ping = check
+            .last_ping
+            .as_deref()
+            .map(|date| {
+                date.parse::<Timestamp>()
+                    .map_err(|_| HealthchecksStateError::InvalidTimestamp(date.to_owned()))
+            })
+            .transpose()?;
+        let status = match check.status.parse()? {
+            Status::Grace => Status::Up,
+            Status::Started if last_ping.is_some() => Status::Up,
+            Status::Started => Status::New,
+            status => status,
+        };
+        Ok(CheckState {
+            manual_resume: check.manual_resume,
+            status,
+            last_ping,
+            ..CheckState::new(period, seconds(check.grace))
+        })
+    }
+
+    /// Keep the check paused when it receives pings, instead of resuming it.
+    #[must_use]
+    pub fn set_manual_resume(mut self, manual_resume: bool) -> CheckState {
+        self.manual_resume = manual_resume;
+        self
+    }
+
+    /// Status of the check at `now`.
+    #[must_use]
+    pub fn status(&self, now: Timestamp) -> Status {
+        if let Some(start) = self.last_start {
+            return if now >= start.saturating_add(self.grace).unwrap_or(start) {
+                Status::Down
+            } else {
+                Status::Started
+            };
+        }
+        match (self.status, self.grace_start()) {
+            (Status::Up, Some(grace_start)) => {
+                if now >= self.add_grace(grace_start) {
+                    Status::Down
+                } else if now >= grace_start {
+                    Status::Grace
+                } else {
+                    Status::Up
+                }
+            }
+            (status, _) => status,
+        }
+    }
+
+    /// Time at which the check is due for its next ping and enters its grace
+    /// time. Is [`None`] unless the check is up.
+    #[must_use]
+    pub fn grace_start(&self) -> Option<Timestamp> {
+        if self.status != Status::Up {
+            return None;
+        }
+        let last_ping = self.last_ping?;
+        match &self.period {
+            Period::Simple { timeout } => Some(
+                last_ping
+                    .saturating_add(to_signed(*timeout))
+                    .unwrap_or(last_ping),
+            ),
+            Period::Cron { schedule } => schedule.next_after(last_ping),
+        }
+    }
+
+    /// Time at which the check goes down unless it receives a ping. Is [`None`]
+    /// if the check is already down, or if it is new or paused and not running.
+    #[must_use]
+    pub fn going_down_after(&self) -> Option<Timestamp> {
+        if self.status == Status::Down {
+            return None;
+        }
+        if let Some(start) = self.last_start {
+            return Some(self.add_grace(start));
+        }
+        self.grace_start()
+            .map(|grace_start| self.add_grace(grace_start))
+    }
+
+    /// Time of the last success or failure ping.
+    #[must_use]
+    pub fn last_ping(&self) -> Option<Timestamp> {
+        self.last_ping
+    }
+
+    /// Time of the last start ping that has not been followed by a success or
+    /// failure ping with the same run ID.
+    #[must_use]
+    pub fn last_start(&self) -> Option<Timestamp> {
+        self.last_start
+    }
+
+    /// Every status change of the check, oldest first.
+    #[must_use]
+    pub fn flips(&self) -> &[Flip] {
+        &self.flips
+    }
+
+    /// Mark the check as down if it missed its deadline by `now`, the same way
+    /// the server does periodically. Returns the resulting flip, which is dated
+    /// at the deadline rather than at `now`.
+    pub fn advance(&mut self, now: Timestamp) -> Option<Flip> {
+        let deadline = self.going_down_after()?;
+        if now < deadline {
+            return None;
+        }
+        self.set_status(Status::Down, deadline)
+    }
+
+    /// Apply a ping received at `at` with an optional run ID. Returns the
+    /// resulting flips, which include the check going down before the ping
+    /// arrived if it was overdue.
+    pub fn ping(&mut self, kind: PingKind, run_id: Option<&str>, at: Timestamp) -> Vec<Flip> {
+        let mut flips: Vec<Flip> = self.advance(at).into_iter().collect();
+        if self.status == Status::Paused && self.manual_resume {
+            return flips;
+        }
+        let new_status = match kind {
+            PingKind::Start => {
+                self.last_start = Some(at);
+                self.last_start_rid = run_id.map(str::to_owned);
+                return flips;
+            }
+            PingKind::Log => return flips,
+            PingKind::Success | PingKind::ExitCode(0) => Status::Up,
+            PingKind::Fail | PingKind::ExitCode(_) => Status::Down,
+        };
+        flips.extend(self.set_status(new_status, at));
+        self.last_ping = Some(at);
+        if self.last_start_rid.as_deref() == run_id {
+            self.last_start = None;
+            self.last_start_rid = None;
+        }
+        flips
+    }
+
+    /// Pause monitoring of the check at `at`. Returns the resulting flip.
+    pub fn pause(&mut self, at: Timestamp) -> Option<Flip> {
+        self.last_start = None;
+        self.last_start_rid = None;
+        self.set_status(Status::Paused, at)
+    }
+
+    /// Resume monitoring of a paused check at `at`, which makes it new again.
+    /// Returns the resulting flip.
+    pub fn resume(&mut self, at: Timestamp) -> Option<Flip> {
+        if self.status != Status::Paused {
+            return None;
+        }
+        self.last_ping = None;
+        self.set_status(Status::New, at)
+    }
+
+    fn add_grace(&self, timestamp: Timestamp) -> Timestamp {
+        timestamp.saturating_add(self.grace).unwrap_or(timestamp)
+    }
+
+    fn set_status(&mut self, status: Status, at: Timestamp) -> Option<Flip> {
+        if self.status == status {
+            return None;
+        }
+        self.status = status;
+        let flip = Flip {
+            timestamp: format_timestamp(at),
+            up: i64::from(status == Status::Up),
+        };
+        self.flips.push(flip.clone());
+        Some(flip)
+    }
+}
+
+/// Format a timestamp the same way the healthchecks.io API does.
+fn format_timestamp(timestamp: Timestamp) -> String {
+    timestamp.strftime("%Y-%m-%dT%H:%M:%S+00:00").to_string()
+}
+
+fn seconds(value: i64) -> Duration {
+    Duration::from_secs(value.max(0).unsigned_abs())
+}
+
+fn to_signed(duration: Duration) -> SignedDuration {
+    SignedDuration::try_from(duration).unwrap_or(SignedDuration::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: i64) -> Timestamp {
+        "2024-01-01T00:00:00Z"
+            .parse::<Timestamp>()
+            .unwrap()
+            .saturating_add(SignedDuration::from_mins(minutes))
+            .unwrap()
+    }
+
+    fn hourly() -> CheckState {
+        CheckState::new(
+            Period::Simple {
+                timeout: Duration::from_secs(3600),
+            },
+            Duration::from_secs(600),
+        )
+    }
+
+    #[test]
+    fn first_ping_flips_new_check_up() {
+        let mut state = hourly();
+        assert_eq!(state.status(at(0)), Status::New);
+        let flips = state.ping(PingKind::Success, None, at(0));
+        assert_eq!(flips.len(), 1);
+        assert_eq!(flips[0].timestamp, "2024-01-01T00:00:00+00:00");
+        assert_eq!(flips[0].up, 1);
+        assert!(state.ping(PingKind::Success, None, at(30)).is_empty());
+    }
+
+    #[test]
+    fn late_check_goes_through_grace_to_down() {
+        let mut state = hourly();
+        state.ping(PingKind::Success, None, at(0));
+        assert_eq!(state.status(at(59)), Status::Up);
+        assert_eq!(state.status(at(60)), Status::Grace);
+        assert_eq!(state.status(at(70)), Status::Down);
+        assert_eq!(state.going_down_after(), Some(at(70)));
+        assert!(state.advance(at(65)).is_none());
+        let flip = state.advance(at(90)).unwrap();
+        assert_eq!(flip.timestamp, "2024-01-01T01:10:00+00:00");
+        assert_eq!(flip.up, 0);
+        assert_eq!(state.going_down_after(), None);
+    }
+
+    #[test]
+    fn late_ping_records_both_flips() {
+        let mut state = hourly();
+        state.ping(PingKind::Success, None, at(0));
+        let flips = state.ping(PingKind::Success, None, at(120));
+        assert_eq!(flips.len(), 2);
+        assert_eq!(flips[0].up, 0);
+        assert_eq!(flips[1].up, 1);
+        assert_eq!(state.flips().len(), 3);
+    }
+
+    #[test]
+    fn exit_codes_map_to_success_and_failure() {
+        let mut state = hourly();
+        state.ping(PingKind::ExitCode(0), None, at(0));
+        assert_eq!(state.status(at(1)), Status::Up);
+        state.ping(PingKind::ExitCode(2), None, at(2));
+        assert_eq!(state.status(at(3)), Status::Down);
+        state.ping(PingKind::Log, None, at(4));
+        assert_eq!(state.status(at(5)), Status::Down);
+    }
+
+    #[test]
+    fn start_without_finish_goes_down_after_grace() {
+        let mut state = hourly();
+        state.ping(PingKind::Success, None, at(0));
+        state.ping(PingKind::Start, None, at(10));
+        assert_eq!(state.status(at(15)), Status::Started);
+        assert_eq!(state.going_down_after(), Some(at(20)));
+        assert_eq!(state.status(at(20)), Status::Down);
+        let flip = state.advance(at(25)).unwrap();
+        assert_eq!(flip.timestamp, "2024-01-01T00:20:00+00:00");
+        state.ping(PingKind::Success, None, at(30));
+        assert_eq!(state.status(at(31)), Status::Up);
+        assert_eq!(state.last_start(), None);
+    }
+
+    #[test]
+    fn start_on_new_check_can_go_down() {
+        let mut state = hourly();
+        state.ping(PingKind::Start, None, at(0));
+        assert_eq!(state.status(at(5)), Status::Started);
+        assert!(state.advance(at(10)).is_some());
+        assert_eq!(state.status(at(11)), Status::Down);
+    }
+
+    #[test]
+    fn finish_with_other_run_id_keeps_check_started() {
+        let mut state = hourly();
+        state.ping(PingKind::Start, Some("a"), at(0));
+        state.ping(PingKind::Start, Some("b"), at(1));
+        state.ping(PingKind::Success, Some("a"), at(2));
+        assert_eq!(state.last_start(), Some(at(1)));
+        assert_eq!(state.status(at(3)), Status::Started);
+        state.ping(PingKind::Success, Some("b"), at(4));
+        assert_eq!(state.last_start(), None);
+        assert_eq!(state.status(at(5)), Status::Up);
+    }
+
+    #[test]
+    fn finish_without_run_id_only_matches_start_without_run_id() {
+        let mut state = hourly();
+        state.ping(PingKind::Start, Some("a"), at(0));
+        state.ping(PingKind::Success, None, at(1));
+        assert_eq!(state.status(at(2)), Status::Started);
+        state.ping(PingKind::Start, None, at(3));
+        state.ping(PingKind::Fail, None, at(4));
+        assert_eq!(state.status(at(5)), Status::Down);
+    }
+
+    #[test]
+    fn paused_check_resumes_on_ping_unless_manual() {
+        let mut state = hourly();
+        state.ping(PingKind::Success, None, at(0));
+        assert_eq!(state.pause(at(1)).unwrap().up, 0);
+        assert_eq!(state.status(at(200)), Status::Paused);
+        assert!(state.advance(at(200)).is_none());
+        state.ping(PingKind::Success, None, at(201));
+        assert_eq!(state.status(at(202)), Status::Up);
+
+        let mut state = hourly().set_manual_resume(true);
+        state.pause(at(0));
+        assert!(state.ping(PingKind::Success, None, at(1)).is_empty());
+        assert_eq!(state.status(at(2)), Status::Paused);
+        state.resume(at(3));
+        assert_eq!(state.status(at(4)), Status::New);
+    }
+
+    #[test]
+    fn builds_state_from_check() {
+        let check: Check = serde_json::from_value(serde_json::json!({
+            "name": "backup",
+            "tags": "",
+            "desc": "",
+            "grace": 600,
+            "n_pings": 1,
+            "status": "up",
+            "started": false,
+            "slug": "backup",
+            "last_ping": "2024-01-01T00:00:00+00:00",
+            "manual_resume": false,
+            "timeout": 3600,
+        }))
+        .unwrap();
+        let state = CheckState::from_check(&check).unwrap();
+        assert_eq!(state.status(at(65)), Status::Grace);
+
+        let check = Check {
+            timeout: None,
+            schedule: Some("0 * * * *".to_owned()),
+            tz: Some("Europe/Atlantis".to_owned()),
+            ..check
+        };
+        assert!(matches!(
+            CheckState::from_check(&check),
+            Err(HealthchecksStateError::InvalidSchedule(_))
+        ));
+    }
+
+    #[test]
+    fn cron_check_is_due_at_next_match() {
+        let mut state = CheckState::new(
+            Period::Cron {
+                schedule: Schedule::new("30 */2 * * *", "UTC").unwrap(),
+            },
+            Duration::from_secs(600),
+        );
+        state.ping(PingKind::Success, None, at(35));
+        assert_eq!(state.grace_start(), Some(at(150)));
+        assert_eq!(state.status(at(149)), Status::Up);
+        assert_eq!(state.status(at(155)), Status::Grace);
+        assert_eq!(state.going_down_after(), Some(at(160)));
+    }
+}
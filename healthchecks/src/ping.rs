@@ -1,14 +1,24 @@
-use crate::{errors::HealthchecksConfigError, DEFAULT_USER_AGENT};
+use crate::{
+    env::{
+        self, HEALTHCHECKS_CHECK_ID, HEALTHCHECKS_PING_API_URL, HEALTHCHECKS_PING_KEY,
+        HEALTHCHECKS_PING_URL, HEALTHCHECKS_RETRIES, HEALTHCHECKS_SPOOL_DIR, HEALTHCHECKS_TIMEOUT,
+        HEALTHCHECKS_USERAGENT,
+    },
+    errors::HealthchecksConfigError,
+    spool::{Spool, SpooledPing},
+    DEFAULT_USER_AGENT,
+};
+use serde_derive::{Deserialize, Serialize};
 use std::result::Result;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use ureq::{Agent, AgentBuilder};
 use uuid::Uuid;
 
-const HEALTHCHECK_PING_URL: &str = "https://hc-ping.com";
+pub(crate) const HEALTHCHECK_PING_URL: &str = "https://hc-ping.com";
 // This number is sourced from a blog post on healthchecks.io that attempts
 // a statistical analysis of what cURL options improve reliability by the biggest
 // factor: https://blog.healthchecks.io/2020/01/fighting-packet-loss-with-curl/
-const MAX_RETRIES: i8 = 20;
+const MAX_RETRIES: u8 = 20;
 
 /// Client type for communication with the healthchecks.io ping API for a single
 /// check.
@@ -18,6 +28,38 @@ pub struct PingClient {
     pub(crate) user_agent: String,
     pub(crate) ureq_agent: Agent,
     pub(crate) api_url: String,
+    pub(crate) spool: Option<Spool>,
+    pub(crate) retries: u8,
+}
+
+/// The kind of signal sent to healthchecks.io by a ping.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PingKind {
+    /// Signals that the job completed successfully.
+    Success,
+    /// Signals that the job failed.
+    Fail,
+    /// Signals that the job has started, to measure its run time.
+    Start,
+    /// Logs an event without changing the status of the check.
+    Log,
+    /// Reports the exit status of the job, where zero signals success and anything
+    /// else signals failure.
+    ExitCode(u8),
+}
+
+impl PingKind {
+    /// Suffix appended to the check's ping URL for this kind of ping.
+    pub(crate) fn path(self) -> String {
+        match self {
+            PingKind::Success => String::new(),
+            PingKind::Fail => "/fail".to_owned(),
+            PingKind::Start => "/start".to_owned(),
+            PingKind::Log => "/log".to_owned(),
+            PingKind::ExitCode(code) => format!("/{code}"),
+        }
+    }
 }
 
 /// Create an instance of [`PingClient`] from the UUID of a check.
@@ -45,17 +87,47 @@ pub fn get_client_with_url(
     } else if api_url.is_empty() {
         Err(HealthchecksConfigError::EmptyApiUrl)
     } else {
-        Ok(PingClient {
-            uuid: uuid.to_owned(),
-            user_agent: DEFAULT_USER_AGENT.to_string(),
-            ureq_agent: AgentBuilder::new().timeout(Duration::from_secs(5)).build(),
-            api_url: api_url.to_owned(),
-        })
+        Ok(PingClient::new(uuid.to_owned(), api_url, default_agent()))
     }
 }
 
+/// Split a full ping URL into the base URL of the pinging API and the path that
+/// identifies the check.
+fn split_ping_url(url: &str) -> Option<(String, String)> {
+    let url = url.trim();
+    let url = url.split(['?', '#']).next()?.trim_end_matches('/');
+    let (scheme, rest) = url.split_once("://")?;
+    if !matches!(scheme, "http" | "https") {
+        return None;
+    }
+    let mut segments: Vec<&str> = rest.split('/').collect();
+    // The host, and either a UUID or a ping key and slug
+    if segments.len() < 2 || segments[0].is_empty() {
+        return None;
+    }
+    let last = segments.pop()?;
+    let path = if Uuid::parse_str(last).is_ok() {
+        last.to_owned()
+    } else {
+        if segments.len() < 2 || last.is_empty() {
+            return None;
+        }
+        let key = segments.pop()?;
+        if key.is_empty() {
+            return None;
+        }
+        format!("{key}/{last}")
+    };
+    Some((format!("{scheme}://{}", segments.join("/")), path))
+}
+
+/// The [`Agent`] used for pinging unless configured otherwise.
+pub(crate) fn default_agent() -> Agent {
+    AgentBuilder::new().timeout(Duration::from_secs(5)).build()
+}
+
 /// Helper method to add `run_id` to URL if it's provided
-fn maybe_add_run_id(url: String, run_id: Option<&Uuid>) -> String {
+pub(crate) fn maybe_add_run_id(url: String, run_id: Option<&str>) -> String {
     if let Some(rid) = run_id {
         format!("{url}?rid={rid}")
     } else {
@@ -63,7 +135,105 @@ fn maybe_add_run_id(url: String, run_id: Option<&Uuid>) -> String {
     }
 }
 
+/// Make a single attempt at delivering a ping, sending `body` with a POST
+/// request if it is provided.
+pub(crate) fn deliver(
+    agent: &Agent,
+    user_agent: &str,
+    url: &str,
+    body: Option<&str>,
+) -> Result<(), Box<ureq::Error>> {
+    let result = if let Some(data) = body {
+        agent
+            .post(url)
+            .set("User-Agent", user_agent)
+            .send_string(data)
+    } else {
+        agent.get(url).set("User-Agent", user_agent).call()
+    };
+    result.map(|_| ()).map_err(Box::new)
+}
+
 impl PingClient {
+    /// Create a client pinging `{api_url}/{path}`, where `path` is either a UUID
+    /// or a ping key and slug separated by a `/`.
+    pub(crate) fn new(path: String, api_url: &str, ureq_agent: Agent) -> PingClient {
+        PingClient {
+            uuid: path,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            ureq_agent,
+            api_url: api_url.to_owned(),
+            spool: None,
+            retries: MAX_RETRIES,
+        }
+    }
+
+    /// Create a client from the full ping URL of a check, as shown on the healthchecks
+    /// dashboard or returned by [`Check::ping_url`](crate::model::Check::ping_url).
+    /// Both UUID-style (`https://hc-ping.com/<uuid>`) and slug-style
+    /// (`https://hc-ping.com/<ping key>/<slug>`) URLs are supported, including those
+    /// of self-hosted instances served under a path prefix.
+    ///
+    /// ```rust
+    /// # use healthchecks::ping::PingClient;
+    /// #
+    /// let client = PingClient::from_url("https://hc.example.com/ping/fqOOd6-F4MMNuCEnzTU01w/db-backup");
+    /// assert!(client.is_ok());
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksConfigError::InvalidPingUrl`] if `url` is not an HTTP(S) URL
+    ///   ending in either a UUID or a ping key and slug.
+    pub fn from_url(url: &str) -> Result<PingClient, HealthchecksConfigError> {
+        let (api_url, path) = split_ping_url(url)
+            .ok_or_else(|| HealthchecksConfigError::InvalidPingUrl(url.to_owned()))?;
+        Ok(PingClient::new(path, &api_url, default_agent()))
+    }
+
+    /// Create a client configured from the environment variables documented in
+    /// [`env`](crate::env). The check is taken from `HEALTHCHECKS_PING_URL` if set,
+    /// otherwise from `HEALTHCHECKS_CHECK_ID` along with `HEALTHCHECKS_PING_KEY` for
+    /// slugs and `HEALTHCHECKS_PING_API_URL` for self-hosted instances.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksConfigError::MissingEnvVar`] if neither `HEALTHCHECKS_PING_URL`
+    ///   nor `HEALTHCHECKS_CHECK_ID` is set, or if `HEALTHCHECKS_CHECK_ID` is a slug and
+    ///   `HEALTHCHECKS_PING_KEY` is not set.
+    /// - Returns [`HealthchecksConfigError::InvalidEnvVar`] if `HEALTHCHECKS_TIMEOUT` or
+    ///   `HEALTHCHECKS_RETRIES` is not a number.
+    /// - Returns [`HealthchecksConfigError::InvalidPingUrl`] if `HEALTHCHECKS_PING_URL` is invalid.
+    pub fn from_env() -> Result<PingClient, HealthchecksConfigError> {
+        let mut client = if let Some(url) = env::optional(env::var, HEALTHCHECKS_PING_URL) {
+            PingClient::from_url(&url)?
+        } else {
+            let check_id = env::required(env::var, HEALTHCHECKS_CHECK_ID)?;
+            let path = if Uuid::parse_str(&check_id).is_ok() {
+                check_id
+            } else {
+                format!(
+                    "{}/{check_id}",
+                    env::required(env::var, HEALTHCHECKS_PING_KEY)?
+                )
+            };
+            let api_url = env::optional(env::var, HEALTHCHECKS_PING_API_URL)
+                .unwrap_or_else(|| HEALTHCHECK_PING_URL.to_owned());
+            PingClient::new(path, &api_url, default_agent())
+        };
+        if let Some(user_agent) = env::optional(env::var, HEALTHCHECKS_USERAGENT) {
+            client = client.set_user_agent(&user_agent);
+        }
+        if let Some(timeout) = env::parse(env::var, HEALTHCHECKS_TIMEOUT)? {
+            client = client.set_timeout(Duration::from_secs(timeout));
+        }
+        if let Some(retries) = env::parse(env::var, HEALTHCHECKS_RETRIES)? {
+            client = client.set_retries(retries);
+        }
+        if let Some(dir) = env::optional(env::var, HEALTHCHECKS_SPOOL_DIR) {
+            client = client.set_spool(Spool::new(dir));
+        }
+        Ok(client)
+    }
+
     /// Set the user agent for the given config
     #[must_use]
     pub fn set_user_agent(mut self, user_agent: &str) -> PingClient {
@@ -71,6 +241,28 @@ impl PingClient {
         self
     }
 
+    /// Set the timeout for every request made by this client. Defaults to 5 seconds.
+    #[must_use]
+    pub fn set_timeout(mut self, timeout: Duration) -> PingClient {
+        self.ureq_agent = AgentBuilder::new().timeout(timeout).build();
+        self
+    }
+
+    /// Set the number of times a ping is retried before giving up. Defaults to 20.
+    #[must_use]
+    pub fn set_retries(mut self, retries: u8) -> PingClient {
+        self.retries = retries;
+        self
+    }
+
+    /// Persist pings that could not be delivered to the given [`Spool`], so they
+    /// can be delivered by a later invocation instead of being lost.
+    #[must_use]
+    pub fn set_spool(mut self, spool: Spool) -> PingClient {
+        self.spool = Some(spool);
+        self
+    }
+
     /// Report success to healthchecks.io. Returns a boolean indicating whether the request succeeded.
     ///
     /// # Example usage with timer
@@ -111,23 +303,7 @@ impl PingClient {
     /// ```
     #[must_use]
     pub fn report_success_with_run_id(&self, run_id: Option<&Uuid>) -> bool {
-        let mut retries: i8 = 0;
-        let url = maybe_add_run_id(format!("{}/{}", self.api_url, self.uuid), run_id);
-        let request = self
-            .ureq_agent
-            .get(&url)
-            .set("User-Agent", &self.user_agent);
-        while retries < MAX_RETRIES {
-            let resp = request.clone().call();
-            if resp.is_ok() {
-                return true;
-            }
-            retries += 1;
-        }
-        if request.call().is_ok() {
-            return true;
-        }
-        false
+        self.send(PingKind::Success, None, run_id)
     }
 
     /// Report failure to healthchecks.io. Returns a boolean indicating whether the request succeeded.
@@ -143,23 +319,7 @@ impl PingClient {
     /// instances of the same job run concurrently.
     #[must_use]
     pub fn report_failure_with_run_id(&self, run_id: Option<&Uuid>) -> bool {
-        let mut retries: i8 = 0;
-        let url = maybe_add_run_id(format!("{}/{}/fail", self.api_url, self.uuid), run_id);
-        let request = self
-            .ureq_agent
-            .get(&url)
-            .set("User-Agent", &self.user_agent);
-        while retries < MAX_RETRIES {
-            let resp = request.clone().call();
-            if resp.is_ok() {
-                return true;
-            }
-            retries += 1;
-        }
-        if request.call().is_ok() {
-            return true;
-        }
-        false
+        self.send(PingKind::Fail, None, run_id)
     }
 
     /// Report failure to healthchecks.io with an accompanying log snippet to help debug the failure. Returns
@@ -189,23 +349,7 @@ impl PingClient {
     /// instances of the same job run concurrently.
     #[must_use]
     pub fn report_failure_with_logs_and_run_id(&self, data: &str, run_id: Option<&Uuid>) -> bool {
-        let mut retries: i8 = 0;
-        let url = maybe_add_run_id(format!("{}/{}/fail", self.api_url, self.uuid), run_id);
-        let request = self
-            .ureq_agent
-            .post(&url)
-            .set("User-Agent", &self.user_agent);
-        while retries < MAX_RETRIES {
-            let resp = request.clone().send_string(data);
-            if resp.is_ok() {
-                return true;
-            }
-            retries += 1;
-        }
-        if request.send_string(data).is_ok() {
-            return true;
-        }
-        false
+        self.send(PingKind::Fail, Some(data), run_id)
     }
 
     /// Start a timer on healthchecks.io, to measure script run times. Official documentation for it is available [here](https://healthchecks.io/docs/measuring_script_run_time/).
@@ -221,22 +365,128 @@ impl PingClient {
     /// instances of the same job run concurrently.
     #[must_use]
     pub fn start_timer_with_run_id(&self, run_id: Option<&Uuid>) -> bool {
-        let mut retries: i8 = 0;
-        let url = maybe_add_run_id(format!("{}/{}/start", self.api_url, self.uuid), run_id);
-        let request = self
-            .ureq_agent
-            .get(&url)
-            .set("User-Agent", &self.user_agent);
-        while retries < MAX_RETRIES {
-            let resp = request.clone().call();
-            if resp.is_ok() {
+        self.send(PingKind::Start, None, run_id)
+    }
+
+    /// Attempt to deliver all pings spooled for this check, in the order they were
+    /// originally sent. Returns a boolean indicating whether the spool for this check
+    /// is now empty. Always returns `true` if no spool is configured.
+    #[must_use]
+    pub fn flush_spool(&self) -> bool {
+        match self.spool {
+            Some(ref spool) => spool
+                .flush_check(&self.uuid, &self.ureq_agent, &self.user_agent)
+                .is_ok_and(|result| result.pending == 0),
+            None => true,
+        }
+    }
+
+    /// Send a ping of any kind, optionally with a body and a run ID, retrying on
+    /// failure. Returns a boolean indicating whether the request succeeded.
+    ///
+    /// If a spool is configured, previously undelivered pings for this check are
+    /// flushed first and this ping is spooled if it cannot be delivered, or if
+    /// older pings are still waiting in the spool.
+    ///
+    /// ```rust
+    /// # use healthchecks::ping::{get_client, PingKind};
+    /// #
+    /// # let client = get_client("2d0a34bd-854d-490e-be2c-1493f7053460").unwrap();
+    /// client.send(PingKind::ExitCode(3), Some("disk full"), None);
+    /// ```
+    #[must_use]
+    pub fn send(&self, kind: PingKind, body: Option<&str>, run_id: Option<&Uuid>) -> bool {
+        let run_id = run_id.map(ToString::to_string);
+        if !self.flush_spool() {
+            self.spool_ping(kind, body, run_id);
+            return false;
+        }
+        let url = maybe_add_run_id(
+            format!("{}/{}{}", self.api_url, self.uuid, kind.path()),
+            run_id.as_deref(),
+        );
+        let mut retries: u8 = 0;
+        while retries < self.retries {
+            if deliver(&self.ureq_agent, &self.user_agent, &url, body).is_ok() {
                 return true;
             }
             retries += 1;
         }
-        if request.call().is_ok() {
+        if deliver(&self.ureq_agent, &self.user_agent, &url, body).is_ok() {
             return true;
         }
+        self.spool_ping(kind, body, run_id);
         false
     }
+
+    /// Persist a ping to the spool, if one is configured.
+    fn spool_ping(&self, kind: PingKind, body: Option<&str>, run_id: Option<String>) {
+        if let Some(ref spool) = self.spool {
+            let ping = SpooledPing {
+                check_url: format!("{}/{}", self.api_url, self.uuid),
+                kind,
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs()),
+                run_id,
+                body: body.map(ToOwned::to_owned),
+            };
+            // The ping is reported as undelivered either way, so there is nothing
+            // more useful to do with a failure to write it to disk.
+            let _ = spool.enqueue(&self.uuid, &ping);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_ping_url;
+
+    #[test]
+    fn splits_uuid_url() {
+        assert_eq!(
+            split_ping_url("https://hc-ping.com/2d0a34bd-854d-490e-be2c-1493f7053460"),
+            Some((
+                "https://hc-ping.com".to_owned(),
+                "2d0a34bd-854d-490e-be2c-1493f7053460".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn splits_self_hosted_slug_url() {
+        assert_eq!(
+            split_ping_url("https://hc.example.com/ping/fqOOd6-F4MMNuCEnzTU01w/db-backup/"),
+            Some((
+                "https://hc.example.com/ping".to_owned(),
+                "fqOOd6-F4MMNuCEnzTU01w/db-backup".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn splits_self_hosted_uuid_url_with_query() {
+        assert_eq!(
+            split_ping_url("http://localhost:8000/ping/2d0a34bd-854d-490e-be2c-1493f7053460?rid=1"),
+            Some((
+                "http://localhost:8000/ping".to_owned(),
+                "2d0a34bd-854d-490e-be2c-1493f7053460".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_urls() {
+        assert_eq!(
+            split_ping_url("hc-ping.com/2d0a34bd-854d-490e-be2c-1493f7053460"),
+            None
+        );
+        assert_eq!(
+            split_ping_url("ftp://hc-ping.com/2d0a34bd-854d-490e-be2c-1493f7053460"),
+            None
+        );
+        assert_eq!(split_ping_url("https://hc-ping.com/db-backup"), None);
+        assert_eq!(split_ping_url("https://hc-ping.com"), None);
+        assert_eq!(split_ping_url("https:///key/slug"), None);
+    }
 }
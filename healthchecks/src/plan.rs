@@ -0,0 +1,839 @@
+use crate::errors::{HealthchecksPlanError, HealthchecksValidationError};
+use crate::manage::ManageClient;
+use crate::model::{Channel, Channels, Check, NewCheck, UniqueField, UpdatedCheck};
+use crate::schedule::DEFAULT_TIMEZONE;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// The checks that should exist in a project, usually read from a configuration
+/// file.
+///
+/// ```toml
+/// [[checks]]
+/// name = "nightly-backup"
+/// tags = ["prod", "db"]
+/// timeout = 86400
+/// grace = 3600
+/// channels = "*"
+///
+/// [[checks]]
+/// name = "db-vacuum"
+/// schedule = "0 3 * * *"
+/// tz = "Europe/Riga"
+/// channels = ["Email"]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DesiredChecks {
+    /// Every check that should exist.
+    #[serde(default)]
+    pub checks: Vec<DesiredCheck>,
+}
+
+/// A check that should exist in a project. Fields that are [`None`] are not
+/// managed and keep whatever value they have on the server.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredCheck {
+    /// UUID or unique key of the existing check this entry manages, which allows
+    /// renaming it. Takes precedence over `slug` and `name` when matching checks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// Name of the check, which is also used to match it to an existing check.
+    pub name: String,
+
+    /// Slug of the check, used to match it to an existing check before the name.
+    /// Sent to the server when creating or updating the check.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+
+    /// Tags of the check.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+
+    /// Description of the check.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc: Option<String>,
+
+    /// Expected time between pings, in seconds. Cannot be combined with `schedule`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+
+    /// How long to wait after a missed ping before sending an alert, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace: Option<u64>,
+
+    /// Cron expression for the expected pings. Cannot be combined with `timeout`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+
+    /// Timezone of the schedule, defaults to UTC.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tz: Option<String>,
+
+    /// Whether the check stays paused when it receives pings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manual_resume: Option<bool>,
+
+    /// Integration channels of the check, given by ID or name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channels: Option<Channels>,
+}
+
+impl DesiredChecks {
+    /// Parse the desired checks from a TOML document with a `checks` array.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksPlanError::InvalidConfig`] if the document could not be parsed.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(config: &str) -> Result<Self, HealthchecksPlanError> {
+        toml::from_str(config).map_err(|e| HealthchecksPlanError::InvalidConfig(e.to_string()))
+    }
+
+    /// Same as [`from_toml_str`](DesiredChecks::from_toml_str), reading the document
+    /// from the file at `path`.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksPlanError::InvalidConfig`] if the file could not be read or parsed.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_file<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> Result<Self, HealthchecksPlanError> {
+        let path = path.as_ref();
+        let config = std::fs::read_to_string(path).map_err(|e| {
+            HealthchecksPlanError::InvalidConfig(format!("{}: {e}", path.display()))
+        })?;
+        Self::from_toml_str(&config)
+    }
+}
+
+impl DesiredCheck {
+    /// Index of the existing check this entry manages, matched by ID, then by
+    /// slug, then by name. Entries with a slug that matches no check fall back
+    /// to the check with the same name, whose slug is then updated, instead of
+    /// creating a new one. Several checks with the same name cannot be told
+    /// apart, so matching by name fails instead of pruning all but one of them.
+    fn find(&self, current: &[Check]) -> Result<Option<usize>, HealthchecksPlanError> {
+        if let Some(ref id) = self.id {
+            return Ok(current.iter().position(|check| {
+                check.id().as_ref() == Some(id) || check.unique_key.as_ref() == Some(id)
+            }));
+        }
+        if let Some(ref wanted) = self.slug {
+            if let Some(idx) = current.iter().position(|check| slug(check) == Some(wanted)) {
+                return Ok(Some(idx));
+            }
+        }
+        let mut by_name = current
+            .iter()
+            .enumerate()
+            .filter(|(_, check)| check.name == self.name)
+            .map(|(idx, _)| idx);
+        match (by_name.next(), by_name.next()) {
+            (Some(_), Some(_)) => Err(HealthchecksPlanError::DuplicateCheck(self.name.clone())),
+            (idx, _) => Ok(idx),
+        }
+    }
+}
+
+#[cfg(feature = "v3")]
+fn slug(check: &Check) -> Option<&String> {
+    check.slug.as_ref()
+}
+
+#[cfg(not(feature = "v3"))]
+fn slug(_check: &Check) -> Option<&String> {
+    None
+}
+
+/// A single field that differs between the desired and the current check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldChange {
+    /// Name of the field.
+    pub field: &'static str,
+    /// Current value, empty for checks that will be created.
+    pub old: String,
+    /// Desired value.
+    pub new: String,
+}
+
+/// A change to make to a project to reach the desired checks.
+#[derive(Clone, Debug)]
+pub enum Change {
+    /// Create a check that does not exist yet.
+    Create {
+        /// Name of the check.
+        name: String,
+        /// Fields set on the new check.
+        fields: Vec<FieldChange>,
+        /// The check to create.
+        check: NewCheck,
+    },
+    /// Update a check that differs from the desired one.
+    Update {
+        /// UUID of the check.
+        id: String,
+        /// Current name of the check.
+        name: String,
+        /// Fields that will change.
+        fields: Vec<FieldChange>,
+        /// The changes to send.
+        check: UpdatedCheck,
+    },
+    /// Delete a check that is not in the desired checks.
+    Delete {
+        /// UUID of the check.
+        id: String,
+        /// Name of the check.
+        name: String,
+    },
+}
+
+impl Change {
+    /// Make this change through `client`.
+    ///
+    /// # Errors
+    /// - Returns any error from [`ManageClient::upsert_check`], [`ManageClient::update_check`]
+    ///   or [`ManageClient::delete`].
+    pub fn apply(&self, client: &ManageClient) -> Result<(), HealthchecksPlanError> {
+        match self {
+            Change::Create { check, .. } => client.upsert_check(check.clone()).map(|_| ())?,
+            Change::Update { id, check, .. } => {
+                client.update_check(check.clone(), id).map(|_| ())?
+            }
+            Change::Delete { id, .. } => client.delete(id).map(|_| ())?,
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Create { name, fields, .. } => {
+                writeln!(f, "  + create \"{name}\"")?;
+                for change in fields {
+                    writeln!(f, "      {}: {}", change.field, change.new)?;
+                }
+            }
+            Change::Update {
+                id, name, fields, ..
+            } => {
+                writeln!(f, "  ~ update \"{name}\" ({id})")?;
+                for change in fields {
+                    writeln!(
+                        f,
+                        "      {}: {} -> {}",
+                        change.field, change.old, change.new
+                    )?;
+                }
+            }
+            Change::Delete { id, name } => writeln!(f, "  - delete \"{name}\" ({id})")?,
+        }
+        Ok(())
+    }
+}
+
+/// The changes needed to turn the checks of a project into the desired ones.
+///
+/// ```rust
+/// # use healthchecks::plan::{DesiredCheck, Plan};
+/// let desired = vec![DesiredCheck {
+///     name: "nightly-backup".to_owned(),
+///     timeout: Some(86400),
+///     ..Default::default()
+/// }];
+/// let plan = Plan::new(&desired, &[], &[], false).unwrap();
+/// assert_eq!(
+///     plan.to_string(),
+///     "  + create \"nightly-backup\"\n      timeout: 86400\n\nPlan: 1 to create, 0 to update, 0 to delete.\n"
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Plan {
+    changes: Vec<Change>,
+}
+
+impl Plan {
+    /// Compute the changes that turn the `current` checks into the `desired` ones.
+    /// Channels of the desired checks are looked up by ID or name in `channels`.
+    /// Checks that are not desired are only deleted if `prune` is set.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksPlanError::DuplicateCheck`] if several desired checks have the
+    ///   same name or match the same existing check.
+    /// - Returns [`HealthchecksPlanError::UnknownChannel`] if a channel is not in `channels`.
+    /// - Returns [`HealthchecksPlanError::MissingId`] if an existing check has no UUID, which
+    ///   happens with read-only API keys.
+    /// - Returns [`HealthchecksPlanError::InvalidCheck`] if a desired check fails validation.
+    pub fn new(
+        desired: &[DesiredCheck],
+        current: &[Check],
+        channels: &[Channel],
+        prune: bool,
+    ) -> Result<Plan, HealthchecksPlanError> {
+        let mut names = BTreeSet::new();
+        let mut matched = vec![false; current.len()];
+        let mut changes = Vec::new();
+        for check in desired {
+            if !names.insert(&check.name) {
+                return Err(HealthchecksPlanError::DuplicateCheck(check.name.clone()));
+            }
+            match check.find(current)? {
+                Some(idx) if matched[idx] => {
+                    return Err(HealthchecksPlanError::DuplicateCheck(check.name.clone()))
+                }
+                Some(idx) => {
+                    matched[idx] = true;
+                    if let Some(change) = diff(check, &current[idx], channels)? {
+                        changes.push(change);
+                    }
+                }
+                None => changes.push(create(check, channels)?),
+            }
+        }
+        if prune {
+            for (check, _) in current.iter().zip(matched).filter(|(_, m)| !m) {
+                changes.push(Change::Delete {
+                    id: id(check)?,
+                    name: check.name.clone(),
+                });
+            }
+        }
+        Ok(Plan { changes })
+    }
+
+    /// Fetch the checks of the project, and its channels if any desired check needs
+    /// them, and compute the changes that turn them into the `desired` ones.
+    ///
+    /// # Errors
+    /// - Returns any error from [`ManageClient::get_checks`], [`ManageClient::get_channels`]
+    ///   or [`Plan::new`].
+    pub fn fetch(
+        client: &ManageClient,
+        desired: &[DesiredCheck],
+        prune: bool,
+    ) -> Result<Plan, HealthchecksPlanError> {
+        let current = client.get_checks()?;
+        let channels = if desired.iter().any(|c| c.channels.is_some()) {
+            client.get_channels()?
+        } else {
+            Vec::new()
+        };
+        Plan::new(desired, &current, &channels, prune)
+    }
+
+    /// Changes in the plan, in the order they are applied.
+    #[must_use]
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Whether the checks already match the desired ones.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Make every change in the plan through `client`, stopping at the first error.
+    ///
+    /// # Errors
+    /// - Returns any error from [`Change::apply`].
+    pub fn apply(&self, client: &ManageClient) -> Result<(), HealthchecksPlanError> {
+        self.changes
+            .iter()
+            .try_for_each(|change| change.apply(client))
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes, checks match the configuration.");
+        }
+        let (mut create, mut update, mut delete) = (0, 0, 0);
+        for change in &self.changes {
+            match change {
+                Change::Create { .. } => create += 1,
+                Change::Update { .. } => update += 1,
+                Change::Delete { .. } => delete += 1,
+            }
+            write!(f, "{change}")?;
+        }
+        writeln!(
+            f,
+            "\nPlan: {create} to create, {update} to update, {delete} to delete."
+        )
+    }
+}
+
+fn id(check: &Check) -> Result<String, HealthchecksPlanError> {
+    check
+        .id()
+        .ok_or_else(|| HealthchecksPlanError::MissingId(check.name.clone()))
+}
+
+/// Resolve desired channels to the set of their IDs.
+fn resolve_channels(
+    check: &str,
+    desired: &Channels,
+    channels: &[Channel],
+) -> Result<BTreeSet<String>, HealthchecksPlanError> {
+    match desired {
+        Channels::All => Ok(channels.iter().map(|c| c.id.clone()).collect()),
+        Channels::None => Ok(BTreeSet::new()),
+        Channels::List(list) => list
+            .iter()
+            .map(|wanted| {
+                channels
+                    .iter()
+                    .find(|c| c.id == *wanted || c.name == *wanted)
+                    .map(|c| c.id.clone())
+                    .ok_or_else(|| HealthchecksPlanError::UnknownChannel {
+                        check: check.to_owned(),
+                        channel: wanted.clone(),
+                    })
+            })
+            .collect(),
+    }
+}
+
+/// Describe a set of channel IDs by their names.
+fn channel_names(ids: &BTreeSet<String>, channels: &[Channel]) -> String {
+    let names: Vec<&str> = ids
+        .iter()
+        .map(|id| {
+            channels
+                .iter()
+                .find(|c| c.id == *id)
+                .map_or(id.as_str(), |c| c.name.as_str())
+        })
+        .collect();
+    format!("[{}]", names.join(", "))
+}
+
+/// Make sure a desired check does not have both a timeout and a schedule.
+fn check_period(desired: &DesiredCheck) -> Result<(), HealthchecksPlanError> {
+    if desired.timeout.is_some() && desired.schedule.is_some() {
+        return Err(HealthchecksPlanError::InvalidCheck {
+            name: desired.name.clone(),
+            source: HealthchecksValidationError::TimeoutAndSchedule,
+        });
+    }
+    Ok(())
+}
+
+fn seconds(value: u64) -> i32 {
+    i32::try_from(value).unwrap_or(i32::MAX)
+}
+
+fn create(desired: &DesiredCheck, channels: &[Channel]) -> Result<Change, HealthchecksPlanError> {
+    check_period(desired)?;
+    let mut fields = Vec::new();
+    let mut field = |field, new: String| {
+        fields.push(FieldChange {
+            field,
+            old: String::new(),
+            new,
+        });
+    };
+    #[cfg(feature = "v3")]
+    if let Some(ref slug) = desired.slug {
+        field("slug", format!("{slug:?}"));
+    }
+    let tags = desired.tags.as_ref().map(|tags| tags.join(" "));
+    if let Some(ref tags) = tags {
+        field("tags", format!("{tags:?}"));
+    }
+    if let Some(ref desc) = desired.desc {
+        field("desc", format!("{desc:?}"));
+    }
+    if let Some(timeout) = desired.timeout {
+        field("timeout", timeout.to_string());
+    }
+    if let Some(grace) = desired.grace {
+        field("grace", grace.to_string());
+    }
+    if let Some(ref schedule) = desired.schedule {
+        field("schedule", format!("{schedule:?}"));
+    }
+    if let Some(ref tz) = desired.tz {
+        field("tz", format!("{tz:?}"));
+    }
+    if let Some(manual_resume) = desired.manual_resume {
+        field("manual_resume", manual_resume.to_string());
+    }
+    let channels = match desired.channels {
+        Some(ref list @ Channels::List(_)) => {
+            let ids = resolve_channels(&desired.name, list, channels)?;
+            field("channels", channel_names(&ids, channels));
+            Some(ids.into_iter().collect::<Vec<_>>().join(","))
+        }
+        Some(ref all_or_none) => {
+            field("channels", format!("{:?}", all_or_none.to_api_string()));
+            Some(all_or_none.to_api_string())
+        }
+        None => None,
+    };
+    let check = NewCheck {
+        name: Some(desired.name.clone()),
+        tags,
+        desc: desired.desc.clone(),
+        timeout: desired.timeout.map(seconds),
+        grace: desired.grace.map(seconds),
+        schedule: desired.schedule.clone(),
+        tz: desired.tz.clone(),
+        manual_resume: desired.manual_resume,
+        channels,
+        #[cfg(feature = "v3")]
+        slug: desired.slug.clone(),
+        unique: Some(vec![UniqueField::Name.as_str().to_owned()]),
+    };
+    check
+        .validate()
+        .map_err(|source| HealthchecksPlanError::InvalidCheck {
+            name: desired.name.clone(),
+            source,
+        })?;
+    Ok(Change::Create {
+        name: desired.name.clone(),
+        fields,
+        check,
+    })
+}
+
+fn diff(
+    desired: &DesiredCheck,
+    current: &Check,
+    channels: &[Channel],
+) -> Result<Option<Change>, HealthchecksPlanError> {
+    check_period(desired)?;
+    let mut fields = Vec::new();
+    let mut field = |field, old: String, new: String| {
+        fields.push(FieldChange { field, old, new });
+    };
+    let mut check = UpdatedCheck::default();
+
+    if desired.name != current.name {
+        field(
+            "name",
+            format!("{:?}", current.name),
+            format!("{:?}", desired.name),
+        );
+        check.name = Some(desired.name.clone());
+    }
+    #[cfg(feature = "v3")]
+    if let Some(ref wanted) = desired.slug {
+        if current.slug.as_ref() != Some(wanted) {
+            let old = current
+                .slug
+                .as_ref()
+                .map_or_else(|| "-".to_owned(), |s| format!("{s:?}"));
+            field("slug", old, format!("{wanted:?}"));
+            check.slug = Some(wanted.clone());
+        }
+    }
+    if let Some(ref tags) = desired.tags {
+        let wanted: BTreeSet<&str> = tags.iter().map(String::as_str).collect();
+        let existing: BTreeSet<&str> = current.tags.split_whitespace().collect();
+        if wanted != existing {
+            let tags = tags.join(" ");
+            field("tags", format!("{:?}", current.tags), format!("{tags:?}"));
+            check.tags = Some(tags);
+        }
+    }
+    if let Some(ref desc) = desired.desc {
+        if *desc != current.desc {
+            field("desc", format!("{:?}", current.desc), format!("{desc:?}"));
+            check.desc = Some(desc.clone());
+        }
+    }
+    if let Some(timeout) = desired.timeout {
+        let matches = current.schedule.is_none()
+            && current.timeout.and_then(|t| u64::try_from(t).ok()) == Some(timeout);
+        if !matches {
+            let old = match (&current.schedule, current.timeout) {
+                (Some(schedule), _) => format!("{schedule:?}"),
+                (None, Some(timeout)) => timeout.to_string(),
+                (None, None) => "-".to_owned(),
+            };
+            field("timeout", old, timeout.to_string());
+            check.timeout = Some(seconds(timeout));
+        }
+    }
+    if let Some(grace) = desired.grace {
+        if u64::try_from(current.grace).ok() != Some(grace) {
+            field("grace", current.grace.to_string(), grace.to_string());
+            check.grace = Some(seconds(grace));
+        }
+    }
+    if let Some(ref schedule) = desired.schedule {
+        let tz = desired.tz.as_deref().unwrap_or(DEFAULT_TIMEZONE);
+        let current_tz = current.tz.as_deref().unwrap_or(DEFAULT_TIMEZONE);
+        if current.schedule.as_ref() != Some(schedule) {
+            let old = current
+                .schedule
+                .as_ref()
+                .map_or_else(|| "-".to_owned(), |s| format!("{s:?}"));
+            field("schedule", old, format!("{schedule:?}"));
+        }
+        if current_tz != tz {
+            field("tz", format!("{current_tz:?}"), format!("{tz:?}"));
+        }
+        if current.schedule.as_ref() != Some(schedule) || current_tz != tz {
+            check.schedule = Some(schedule.clone());
+            check.tz = Some(tz.to_owned());
+        }
+    }
+    if let Some(manual_resume) = desired.manual_resume {
+        if manual_resume != current.manual_resume {
+            field(
+                "manual_resume",
+                current.manual_resume.to_string(),
+                manual_resume.to_string(),
+            );
+            check.manual_resume = Some(manual_resume);
+        }
+    }
+    if let Some(ref wanted) = desired.channels {
+        let wanted = resolve_channels(&desired.name, wanted, channels)?;
+        let existing: BTreeSet<String> = current
+            .channels
+            .iter()
+            .flat_map(|c| c.split(','))
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(ToOwned::to_owned)
+            .collect();
+        if wanted != existing {
+            field(
+                "channels",
+                channel_names(&existing, channels),
+                channel_names(&wanted, channels),
+            );
+            check.channels = Some(wanted.into_iter().collect::<Vec<_>>().join(","));
+        }
+    }
+
+    if fields.is_empty() {
+        return Ok(None);
+    }
+    check
+        .validate()
+        .map_err(|source| HealthchecksPlanError::InvalidCheck {
+            name: desired.name.clone(),
+            source,
+        })?;
+    Ok(Some(Change::Update {
+        id: id(current)?,
+        name: current.name.clone(),
+        fields,
+        check,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn check(name: &str, id: &str) -> Check {
+        serde_json::from_value(json!({
+            "name": name,
+            "slug": name,
+            "tags": "prod db",
+            "desc": "",
+            "grace": 3600,
+            "n_pings": 0,
+            "status": "new",
+            "started": false,
+            "manual_resume": false,
+            "timeout": 86400,
+            "channels": "c1",
+            "ping_url": format!("https://hc-ping.com/{id}"),
+        }))
+        .unwrap()
+    }
+
+    fn channels() -> Vec<Channel> {
+        ["c1", "c2"]
+            .iter()
+            .zip(["Email", "Slack"])
+            .map(|(id, name)| Channel {
+                id: (*id).to_owned(),
+                name: name.to_owned(),
+                kind: "email".to_owned(),
+            })
+            .collect()
+    }
+
+    fn desired(name: &str) -> DesiredCheck {
+        DesiredCheck {
+            name: name.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unmanaged_fields_do_not_change() {
+        let plan = Plan::new(&[desired("backup")], &[check("backup", "1")], &[], true).unwrap();
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn updates_changed_fields() {
+        let wanted = DesiredCheck {
+            tags: Some(vec!["db".to_owned(), "prod".to_owned()]),
+            grace: Some(600),
+            schedule: Some("0 3 * * *".to_owned()),
+            channels: Some(Channels::List(vec!["Slack".to_owned()])),
+            ..desired("backup")
+        };
+        let plan = Plan::new(&[wanted], &[check("backup", "1")], &channels(), false).unwrap();
+        let [Change::Update {
+            id, fields, check, ..
+        }] = plan.changes()
+        else {
+            panic!("expected a single update, got {plan}");
+        };
+        assert_eq!(id, "1");
+        let names: Vec<_> = fields.iter().map(|f| f.field).collect();
+        assert_eq!(names, vec!["grace", "schedule", "channels"]);
+        assert_eq!(check.tags, None);
+        assert_eq!(check.tz.as_deref(), Some("UTC"));
+        assert_eq!(check.channels.as_deref(), Some("c2"));
+        assert!(plan
+            .to_string()
+            .contains("      channels: [Email] -> [Slack]\n"));
+    }
+
+    #[test]
+    fn creates_and_prunes() {
+        let current = [check("backup", "1"), check("old", "2")];
+        let wanted = [desired("backup"), desired("vacuum")];
+        let plan = Plan::new(&wanted, &current, &[], false).unwrap();
+        assert!(matches!(plan.changes(), [Change::Create { name, .. }] if name == "vacuum"));
+
+        let plan = Plan::new(&wanted, &current, &[], true).unwrap();
+        assert!(matches!(
+            plan.changes(),
+            [Change::Create { .. }, Change::Delete { id, .. }] if id == "2"
+        ));
+        assert!(plan
+            .to_string()
+            .ends_with("Plan: 1 to create, 0 to update, 1 to delete.\n"));
+    }
+
+    #[test]
+    fn matches_by_id_and_slug_before_name() {
+        let current = [check("backup", "1")];
+        let by_id = DesiredCheck {
+            id: Some("1".to_owned()),
+            ..desired("nightly-backup")
+        };
+        let plan = Plan::new(&[by_id], &current, &[], true).unwrap();
+        let [Change::Update { fields, .. }] = plan.changes() else {
+            panic!("expected a rename, got {plan}");
+        };
+        assert_eq!(fields[0].field, "name");
+
+        #[cfg(feature = "v3")]
+        {
+            let by_slug = DesiredCheck {
+                slug: Some("backup".to_owned()),
+                ..desired("Backup")
+            };
+            let plan = Plan::new(&[by_slug], &current, &[], true).unwrap();
+            assert!(matches!(plan.changes(), [Change::Update { .. }]));
+        }
+    }
+
+    #[cfg(feature = "v3")]
+    #[test]
+    fn updates_the_slug_of_a_check_matched_by_name() {
+        let wanted = DesiredCheck {
+            slug: Some("nightly".to_owned()),
+            ..desired("backup")
+        };
+        let plan = Plan::new(
+            std::slice::from_ref(&wanted),
+            &[check("backup", "1")],
+            &[],
+            true,
+        )
+        .unwrap();
+        let [Change::Update {
+            id, fields, check, ..
+        }] = plan.changes()
+        else {
+            panic!("expected a single update, got {plan}");
+        };
+        assert_eq!(id, "1");
+        assert_eq!(fields[0].field, "slug");
+        assert_eq!(check.slug.as_deref(), Some("nightly"));
+
+        let plan = Plan::new(&[wanted], &[], &[], false).unwrap();
+        let [Change::Create { check, .. }] = plan.changes() else {
+            panic!("expected a single create, got {plan}");
+        };
+        assert_eq!(check.slug.as_deref(), Some("nightly"));
+    }
+
+    #[test]
+    fn rejects_checks_with_the_same_name() {
+        let current = [check("backup", "1"), check("backup", "2")];
+        let result = Plan::new(&[desired("backup")], &current, &[], true);
+        assert!(
+            matches!(result, Err(HealthchecksPlanError::DuplicateCheck(name)) if name == "backup")
+        );
+
+        let by_id = DesiredCheck {
+            id: Some("2".to_owned()),
+            ..desired("backup")
+        };
+        let plan = Plan::new(&[by_id], &current, &[], true).unwrap();
+        assert!(matches!(plan.changes(), [Change::Delete { id, .. }] if id == "1"));
+    }
+
+    #[test]
+    fn rejects_invalid_configuration() {
+        let result = Plan::new(&[desired("a"), desired("a")], &[], &[], false);
+        assert!(matches!(result, Err(HealthchecksPlanError::DuplicateCheck(name)) if name == "a"));
+
+        let unknown = DesiredCheck {
+            channels: Some(Channels::List(vec!["Pager".to_owned()])),
+            ..desired("a")
+        };
+        let result = Plan::new(&[unknown], &[], &channels(), false);
+        assert!(matches!(
+            result,
+            Err(HealthchecksPlanError::UnknownChannel { channel, .. }) if channel == "Pager"
+        ));
+
+        let both = DesiredCheck {
+            timeout: Some(3600),
+            schedule: Some("@daily".to_owned()),
+            ..desired("a")
+        };
+        assert!(matches!(
+            Plan::new(&[both], &[], &[], false),
+            Err(HealthchecksPlanError::InvalidCheck { .. })
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn parses_toml() {
+        let desired = DesiredChecks::from_toml_str(
+            "[[checks]]\nname = \"backup\"\ntags = [\"prod\"]\nchannels = \"*\"\n\n[[checks]]\nname = \"vacuum\"\nchannels = [\"Email\"]\n",
+        )
+        .unwrap();
+        assert_eq!(desired.checks[0].channels, Some(Channels::All));
+        assert_eq!(
+            desired.checks[1].channels,
+            Some(Channels::List(vec!["Email".to_owned()]))
+        );
+        assert!(DesiredChecks::from_toml_str("[[checks]]\nname = \"a\"\ntimout = 5\n").is_err());
+    }
+}
@@ -0,0 +1,311 @@
+use crate::{
+    env::{self, HEALTHCHECKS_CHECKS, HEALTHCHECKS_PING_KEY},
+    errors::HealthchecksConfigError,
+    ping::{default_agent, PingClient, HEALTHCHECK_PING_URL},
+    spool::Spool,
+};
+use std::collections::BTreeMap;
+use std::result::Result;
+use uuid::Uuid;
+
+/// A collection of [`PingClient`]s for several checks, looked up by a name of
+/// your choosing. All clients share a single HTTP agent and its connection pool.
+///
+/// ```rust
+/// # use healthchecks::registry::PingRegistry;
+/// #
+/// let registry = PingRegistry::new(
+///     [("nightly-backup", "2d0a34bd-854d-490e-be2c-1493f7053460")],
+///     None,
+/// )
+/// .unwrap();
+/// assert!(registry.get("nightly-backup").is_ok());
+/// assert!(registry.get("weekly-backup").is_err());
+/// ```
+pub struct PingRegistry {
+    pub(crate) clients: BTreeMap<String, PingClient>,
+}
+
+/// Configuration file format accepted by [`PingRegistry::from_toml_str`].
+#[cfg(feature = "toml")]
+#[derive(serde_derive::Deserialize)]
+struct RegistryConfig {
+    ping_key: Option<String>,
+    api_url: Option<String>,
+    checks: BTreeMap<String, String>,
+}
+
+impl PingRegistry {
+    /// Create a registry from pairs of names and check identifiers. Identifiers can
+    /// either be UUIDs or slugs, in which case `ping_key` must be provided.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksConfigError::MissingPingKey`] if a check is identified
+    ///   by slug and `ping_key` is [`None`].
+    pub fn new<I, K, V>(checks: I, ping_key: Option<&str>) -> Result<Self, HealthchecksConfigError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: AsRef<str>,
+    {
+        Self::new_with_url(checks, ping_key, HEALTHCHECK_PING_URL)
+    }
+
+    /// Same as [`new`](PingRegistry::new), with the ability to use a custom instance
+    /// of the healthchecks server.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksConfigError::MissingPingKey`] if a check is identified
+    ///   by slug and `ping_key` is [`None`].
+    /// - Returns [`HealthchecksConfigError::EmptyApiUrl`] if `api_url` is empty.
+    pub fn new_with_url<I, K, V>(
+        checks: I,
+        ping_key: Option<&str>,
+        api_url: &str,
+    ) -> Result<Self, HealthchecksConfigError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: AsRef<str>,
+    {
+        if api_url.is_empty() {
+            return Err(HealthchecksConfigError::EmptyApiUrl);
+        }
+        let agent = default_agent();
+        let mut clients = BTreeMap::new();
+        for (name, id) in checks {
+            let name = name.into();
+            let id = id.as_ref().trim();
+            let path = if Uuid::parse_str(id).is_ok() {
+                id.to_owned()
+            } else if let Some(key) = ping_key {
+                format!("{key}/{id}")
+            } else {
+                return Err(HealthchecksConfigError::MissingPingKey(name));
+            };
+            clients.insert(name, PingClient::new(path, api_url, agent.clone()));
+        }
+        Ok(PingRegistry { clients })
+    }
+
+    /// Create a registry from the `HEALTHCHECKS_CHECKS` environment variable, which
+    /// holds comma-separated `name=uuid-or-slug` pairs. Slugs use the ping key from
+    /// `HEALTHCHECKS_PING_KEY`.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksConfigError::MissingEnvVar`] if `HEALTHCHECKS_CHECKS` is not set.
+    /// - Returns [`HealthchecksConfigError::InvalidRegistry`] if `HEALTHCHECKS_CHECKS` is malformed.
+    /// - Returns [`HealthchecksConfigError::MissingPingKey`] if a check is identified
+    ///   by slug and `HEALTHCHECKS_PING_KEY` is not set.
+    pub fn from_env() -> Result<Self, HealthchecksConfigError> {
+        let checks = env::required(env::var, HEALTHCHECKS_CHECKS)?;
+        let ping_key = env::optional(env::var, HEALTHCHECKS_PING_KEY);
+        Self::new(parse_pairs(&checks)?, ping_key.as_deref())
+    }
+
+    /// Create a registry from a TOML document with a `checks` table mapping names to
+    /// UUIDs or slugs, and optional `ping_key` and `api_url` keys.
+    ///
+    /// ```toml
+    /// ping_key = "fqOOd6-F4MMNuCEnzTU01w"
+    ///
+    /// [checks]
+    /// nightly-backup = "2d0a34bd-854d-490e-be2c-1493f7053460"
+    /// db-vacuum = "db-vacuum"
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksConfigError::InvalidRegistry`] if the document could not be parsed.
+    /// - Returns [`HealthchecksConfigError::MissingPingKey`] if a check is identified
+    ///   by slug and no ping key is set.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(config: &str) -> Result<Self, HealthchecksConfigError> {
+        let config: RegistryConfig = toml::from_str(config)
+            .map_err(|e| HealthchecksConfigError::InvalidRegistry(e.to_string()))?;
+        Self::new_with_url(
+            config.checks,
+            config.ping_key.as_deref(),
+            config.api_url.as_deref().unwrap_or(HEALTHCHECK_PING_URL),
+        )
+    }
+
+    /// Same as [`from_toml_str`](PingRegistry::from_toml_str), reading the document
+    /// from the file at `path`.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksConfigError::InvalidRegistry`] if the file could not be
+    ///   read or parsed.
+    /// - Returns [`HealthchecksConfigError::MissingPingKey`] if a check is identified
+    ///   by slug and no ping key is set.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_file<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> Result<Self, HealthchecksConfigError> {
+        let path = path.as_ref();
+        let config = std::fs::read_to_string(path).map_err(|e| {
+            HealthchecksConfigError::InvalidRegistry(format!("{}: {e}", path.display()))
+        })?;
+        Self::from_toml_str(&config)
+    }
+
+    /// Set the user agent for every client in the registry
+    #[must_use]
+    pub fn set_user_agent(mut self, user_agent: &str) -> PingRegistry {
+        self.clients = self
+            .clients
+            .into_iter()
+            .map(|(name, client)| (name, client.set_user_agent(user_agent)))
+            .collect();
+        self
+    }
+
+    /// Persist pings that could not be delivered for any check in the registry to
+    /// the given [`Spool`].
+    #[must_use]
+    pub fn set_spool(mut self, spool: Spool) -> PingRegistry {
+        self.clients = self
+            .clients
+            .into_iter()
+            .map(|(name, client)| (name, client.set_spool(spool.clone())))
+            .collect();
+        self
+    }
+
+    /// Names of all checks in the registry, in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.clients.keys().map(String::as_str)
+    }
+
+    /// Get the [`PingClient`] for the check registered as `name`.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksConfigError::UnknownCheck`] if no check was registered as `name`.
+    pub fn get(&self, name: &str) -> Result<&PingClient, HealthchecksConfigError> {
+        self.clients
+            .get(name)
+            .ok_or_else(|| HealthchecksConfigError::UnknownCheck {
+                name: name.to_owned(),
+                known: self.names().collect::<Vec<_>>().join(", "),
+            })
+    }
+
+    /// Report success for the check registered as `name`. Returns a boolean indicating
+    /// whether the request succeeded.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksConfigError::UnknownCheck`] if no check was registered as `name`.
+    pub fn success(&self, name: &str) -> Result<bool, HealthchecksConfigError> {
+        Ok(self.get(name)?.report_success())
+    }
+
+    /// Report failure for the check registered as `name`. Returns a boolean indicating
+    /// whether the request succeeded.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksConfigError::UnknownCheck`] if no check was registered as `name`.
+    pub fn failure(&self, name: &str) -> Result<bool, HealthchecksConfigError> {
+        Ok(self.get(name)?.report_failure())
+    }
+
+    /// Report failure with an accompanying log snippet for the check registered as
+    /// `name`. Returns a boolean indicating whether the request succeeded.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksConfigError::UnknownCheck`] if no check was registered as `name`.
+    pub fn failure_with_logs(
+        &self,
+        name: &str,
+        data: &str,
+    ) -> Result<bool, HealthchecksConfigError> {
+        Ok(self.get(name)?.report_failure_with_logs(data))
+    }
+
+    /// Start a timer for the check registered as `name`. Returns a boolean indicating
+    /// whether the request succeeded.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksConfigError::UnknownCheck`] if no check was registered as `name`.
+    pub fn start(&self, name: &str) -> Result<bool, HealthchecksConfigError> {
+        Ok(self.get(name)?.start_timer())
+    }
+}
+
+/// Parse comma-separated `name=value` pairs.
+fn parse_pairs(pairs: &str) -> Result<Vec<(String, String)>, HealthchecksConfigError> {
+    pairs
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((name, id)) if !name.trim().is_empty() && !id.trim().is_empty() => {
+                Ok((name.trim().to_owned(), id.trim().to_owned()))
+            }
+            _ => Err(HealthchecksConfigError::InvalidRegistry(format!(
+                "expected name=uuid-or-slug, found '{pair}'"
+            ))),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UUID: &str = "2d0a34bd-854d-490e-be2c-1493f7053460";
+
+    #[test]
+    fn resolves_uuids_and_slugs() {
+        let registry =
+            PingRegistry::new([("backup", UUID), ("vacuum", "db-vacuum")], Some("key")).unwrap();
+        assert_eq!(registry.get("backup").unwrap().uuid, UUID);
+        assert_eq!(registry.get("vacuum").unwrap().uuid, "key/db-vacuum");
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            vec!["backup", "vacuum"]
+        );
+    }
+
+    #[test]
+    fn slug_requires_ping_key() {
+        let result = PingRegistry::new([("vacuum", "db-vacuum")], None);
+        assert!(matches!(
+            result,
+            Err(HealthchecksConfigError::MissingPingKey(name)) if name == "vacuum"
+        ));
+    }
+
+    #[test]
+    fn unknown_name_lists_known_checks() {
+        let registry = PingRegistry::new([("a", UUID), ("b", UUID)], None).unwrap();
+        let err = registry.success("c").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "no check named 'c' in registry, known checks: a, b"
+        );
+    }
+
+    #[test]
+    fn parses_pairs() {
+        let pairs = parse_pairs(&format!(" backup={UUID}, vacuum = db-vacuum,")).unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                ("backup".to_owned(), UUID.to_owned()),
+                ("vacuum".to_owned(), "db-vacuum".to_owned())
+            ]
+        );
+        assert!(parse_pairs("backup").is_err());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn parses_toml() {
+        let registry = PingRegistry::from_toml_str(&format!(
+            "ping_key = \"key\"\napi_url = \"https://hc.example.com/ping\"\n\n[checks]\nbackup = \"{UUID}\"\nvacuum = \"db-vacuum\"\n"
+        ))
+        .unwrap();
+        let vacuum = registry.get("vacuum").unwrap();
+        assert_eq!(vacuum.api_url, "https://hc.example.com/ping");
+        assert_eq!(vacuum.uuid, "key/db-vacuum");
+    }
+}
@@ -0,0 +1,490 @@
+//! Cron expressions are parsed with the same rules as the healthchecks.io server:
+//!
+//! - Five whitespace separated fields: minute, hour, day of month, month and day of week.
+//! - Each field accepts `*`, numbers, ranges (`1-5`), steps (`*/15`, `10-40/10`, `5/10`)
+//!   and comma separated lists of those.
+//! - Months and days of week can be given by their three letter English names, and
+//!   Sunday is both `0` and `7`.
+//! - The day of month accepts `L` for the last day of the month, and the day of week
+//!   accepts `5L` for the last Friday of the month and `5#2` for its second Friday.
+//! - When both the day of month and the day of week are restricted, a day matches if
+//!   either of them does.
+//! - The `@yearly`, `@annually`, `@monthly`, `@weekly`, `@daily`, `@midnight` and
+//!   `@hourly` macros stand in for their equivalent expressions.
+use crate::errors::HealthchecksValidationError;
+use jiff::civil::{Date, DateTime};
+use jiff::tz::TimeZone;
+use jiff::{SignedDuration, Timestamp};
+use std::fmt;
+use std::time::Duration;
+
+/// Timezone used by healthchecks.io when a check does not specify one.
+pub const DEFAULT_TIMEZONE: &str = "UTC";
+
+/// Number of days searched for the next match before giving up, which covers
+/// the 28 year cycle after which February 29th falls on the same weekday again.
+const SEARCH_DAYS: usize = 366 * 29;
+
+/// A cron schedule in a given timezone, which determines when a check is
+/// expected to ping.
+///
+/// ```rust
+/// # use healthchecks::schedule::Schedule;
+/// let schedule = Schedule::new("@daily", "Europe/Riga").unwrap();
+/// let after: jiff::Timestamp = "2024-01-01T12:00:00Z".parse().unwrap();
+/// let next = schedule.next_after(after).unwrap();
+/// assert_eq!(next.to_string(), "2024-01-01T22:00:00Z");
+/// assert!(Schedule::new("0 25 * * *", "UTC").is_err());
+/// assert!(Schedule::new("0 0 * * *", "Mars/Olympus_Mons").is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Schedule {
+    expression: String,
+    timezone: String,
+    tz: TimeZone,
+    cron: Cron,
+}
+
+impl Schedule {
+    /// Parse the cron `expression`, to be evaluated in the IANA timezone `tz`.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksValidationError::InvalidSchedule`] if the expression is
+    ///   malformed or can never match.
+    /// - Returns [`HealthchecksValidationError::InvalidTimezone`] if `tz` is not a known timezone.
+    pub fn new(expression: &str, tz: &str) -> Result<Schedule, HealthchecksValidationError> {
+        let cron = Cron::parse(expression).map_err(|reason| {
+            HealthchecksValidationError::InvalidSchedule {
+                expression: expression.to_owned(),
+                reason,
+            }
+        })?;
+        Ok(Schedule {
+            expression: expression.to_owned(),
+            timezone: tz.to_owned(),
+            tz: timezone(tz)?,
+            cron,
+        })
+    }
+
+    /// The cron expression of the schedule.
+    #[must_use]
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// The name of the timezone the schedule is evaluated in.
+    #[must_use]
+    pub fn timezone(&self) -> &str {
+        &self.timezone
+    }
+
+    /// The first time strictly after `after` at which the schedule matches.
+    ///
+    /// Local times skipped by a daylight saving transition are shifted forward by the
+    /// length of the transition, and local times that occur twice only match the
+    /// first time.
+    #[must_use]
+    pub fn next_after(&self, after: Timestamp) -> Option<Timestamp> {
+        let mut date = after.to_zoned(self.tz.clone()).date();
+        for _ in 0..SEARCH_DAYS {
+            if self.cron.matches_date(date) {
+                for hour in bits(self.cron.hours, 0, 23) {
+                    for minute in bits(self.cron.minutes, 0, 59) {
+                        let local = date.at(hour, minute, 0, 0);
+                        if let Some(timestamp) = self.resolve(local).filter(|ts| *ts > after) {
+                            return Some(timestamp);
+                        }
+                    }
+                }
+            }
+            date = date.tomorrow().ok()?;
+        }
+        None
+    }
+
+    /// The next `count` times strictly after `after` at which the schedule matches.
+    #[must_use]
+    pub fn next_n(&self, after: Timestamp, count: usize) -> Vec<Timestamp> {
+        let mut times = Vec::with_capacity(count);
+        let mut current = after;
+        while times.len() < count {
+            let Some(next) = self.next_after(current) else {
+                break;
+            };
+            times.push(next);
+            current = next;
+        }
+        times
+    }
+
+    /// The latest time a ping can arrive after a ping at `last_ping` before the
+    /// check goes down, which is the next expected ping plus the `grace` period.
+    #[must_use]
+    pub fn latest_acceptable_ping(
+        &self,
+        last_ping: Timestamp,
+        grace: Duration,
+    ) -> Option<Timestamp> {
+        let grace = SignedDuration::try_from(grace).ok()?;
+        self.next_after(last_ping)?.checked_add(grace).ok()
+    }
+
+    fn resolve(&self, local: DateTime) -> Option<Timestamp> {
+        self.tz.to_ambiguous_timestamp(local).compatible().ok()
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.expression, self.timezone)
+    }
+}
+
+/// Look up an IANA timezone by name, such as `Europe/Riga` or `UTC`.
+///
+/// # Errors
+/// - Returns [`HealthchecksValidationError::InvalidTimezone`] if `tz` is not a known timezone.
+pub fn timezone(tz: &str) -> Result<TimeZone, HealthchecksValidationError> {
+    TimeZone::get(tz).map_err(|_| HealthchecksValidationError::InvalidTimezone(tz.to_owned()))
+}
+
+/// Validate an optional schedule and timezone the way the healthchecks.io API
+/// does, where a missing timezone defaults to [`DEFAULT_TIMEZONE`].
+pub(crate) fn validate(
+    schedule: Option<&str>,
+    tz: Option<&str>,
+) -> Result<(), HealthchecksValidationError> {
+    if let Some(tz) = tz {
+        timezone(tz)?;
+    }
+    if let Some(schedule) = schedule {
+        Schedule::new(schedule, tz.unwrap_or(DEFAULT_TIMEZONE))?;
+    }
+    Ok(())
+}
+
+/// A parsed cron expression, with each field stored as a bit set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether `L` was given as a day of month.
+    last_day: bool,
+    /// Weekdays given as `5#2` or `5L`, with 0 standing for the last one in the month.
+    nth_weekdays: Vec<(i8, i8)>,
+    /// Whether the day of month field is unrestricted.
+    any_day: bool,
+    /// Whether the day of week field is unrestricted.
+    any_weekday: bool,
+}
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+impl Cron {
+    fn parse(expression: &str) -> Result<Cron, String> {
+        let expanded = match expression.trim().to_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *".to_owned(),
+            "@monthly" => "0 0 1 * *".to_owned(),
+            "@weekly" => "0 0 * * 0".to_owned(),
+            "@daily" | "@midnight" => "0 0 * * *".to_owned(),
+            "@hourly" => "0 * * * *".to_owned(),
+            macro_ if macro_.starts_with('@') => return Err(format!("unknown macro '{macro_}'")),
+            fields => fields.to_owned(),
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("expected 5 fields, found {}", fields.len()));
+        };
+
+        let mut last_day = false;
+        let days = parse_field(day, "day of month", 1, 31, &[], |part| {
+            if part == "l" {
+                last_day = true;
+                Ok(true)
+            } else {
+                Ok(false)
+            }
+        })?;
+
+        let mut nth_weekdays = Vec::new();
+        let weekdays = parse_field(weekday, "day of week", 0, 7, &WEEKDAYS, |part| {
+            let (weekday, nth) = if let Some((weekday, nth)) = part.split_once('#') {
+                match nth.parse::<i8>() {
+                    Ok(nth @ 1..=5) => (weekday, nth),
+                    _ => return Err(format!("invalid day of week '{part}'")),
+                }
+            } else if let Some(weekday) = part.strip_suffix('l') {
+                (weekday, 0)
+            } else {
+                return Ok(false);
+            };
+            let weekday = parse_value(weekday, "day of week", 0, 7, &WEEKDAYS)?;
+            nth_weekdays.push((i8::try_from(weekday % 7).unwrap_or_default(), nth));
+            Ok(true)
+        })?;
+        // Sunday can be given as both 0 and 7
+        let weekdays = (weekdays | (weekdays >> 7)) & 0x7f;
+
+        let cron = Cron {
+            minutes: parse_field(minute, "minute", 0, 59, &[], |_| Ok(false))?,
+            hours: parse_field(hour, "hour", 0, 23, &[], |_| Ok(false))?,
+            days,
+            months: parse_field(month, "month", 1, 12, &MONTHS, |_| Ok(false))?,
+            weekdays,
+            last_day,
+            nth_weekdays,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        };
+        if !cron.can_match() {
+            return Err("expression never matches".to_owned());
+        }
+        Ok(cron)
+    }
+
+    /// Whether any day of any year matches. Only a day of month that no selected
+    /// month has, such as February 30th, can make an expression never match.
+    fn can_match(&self) -> bool {
+        if self.any_day || !self.any_weekday || self.last_day {
+            return true;
+        }
+        bits(self.months, 1, 12).any(|month| {
+            let longest = Date::new(2024, month, 1).map_or(31, |date| date.days_in_month());
+            bits(self.days, 1, longest).next().is_some()
+        })
+    }
+
+    fn matches_date(&self, date: Date) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = date.day();
+        let days_in_month = date.days_in_month();
+        let day_matches = self.days & (1 << day) != 0 || (self.last_day && day == days_in_month);
+        let weekday = date.weekday().to_sunday_zero_offset();
+        let weekday_matches = self.weekdays & (1 << weekday) != 0
+            || self.nth_weekdays.iter().any(|&(nth_weekday, nth)| {
+                nth_weekday == weekday
+                    && if nth == 0 {
+                        day + 7 > days_in_month
+                    } else {
+                        (day - 1) / 7 + 1 == nth
+                    }
+            });
+        if self.any_day || self.any_weekday {
+            day_matches && weekday_matches
+        } else {
+            day_matches || weekday_matches
+        }
+    }
+}
+
+/// Parse a single cron field into a bit set, where bit `n` is set if the value
+/// `n` matches. `special` is given each comma separated part first and returns
+/// whether it handled it.
+fn parse_field(
+    field: &str,
+    name: &str,
+    min: u8,
+    max: u8,
+    names: &[&str],
+    mut special: impl FnMut(&str) -> Result<bool, String>,
+) -> Result<u64, String> {
+    let mut set = 0u64;
+    for part in field.split(',') {
+        if special(part)? {
+            continue;
+        }
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u8>() {
+                Ok(step) if step > 0 && step <= max => (range, Some(step)),
+                _ => return Err(format!("invalid step in {name} '{part}'")),
+            },
+            None => (part, None),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, name, min, max, names)?,
+                parse_value(end, name, min, max, names)?,
+            )
+        } else {
+            let start = parse_value(range, name, min, max, names)?;
+            (start, if step.is_some() { max } else { start })
+        };
+        if start > end {
+            return Err(format!("invalid range in {name} '{part}'"));
+        }
+        for value in (start..=end).step_by(usize::from(step.unwrap_or(1))) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+fn parse_value(value: &str, name: &str, min: u8, max: u8, names: &[&str]) -> Result<u8, String> {
+    if let Some(idx) = names.iter().position(|n| *n == value) {
+        return Ok(u8::try_from(idx).unwrap_or_default() + min);
+    }
+    match value.parse::<u8>() {
+        Ok(value) if (min..=max).contains(&value) => Ok(value),
+        _ => Err(format!("invalid {name} '{value}'")),
+    }
+}
+
+/// Iterate over the values between `min` and `max` that are set in `set`.
+fn bits(set: u64, min: i8, max: i8) -> impl Iterator<Item = i8> {
+    (min..=max).filter(move |n| set & (1 << n) != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(value: &str) -> Timestamp {
+        value.parse().unwrap()
+    }
+
+    fn next_n(expression: &str, tz: &str, after: &str, count: usize) -> Vec<String> {
+        Schedule::new(expression, tz)
+            .unwrap()
+            .next_n(ts(after), count)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn expands_macros() {
+        assert_eq!(Cron::parse("@daily"), Cron::parse("0 0 * * *"));
+        assert_eq!(Cron::parse("@ANNUALLY"), Cron::parse("0 0 1 1 *"));
+        assert!(Cron::parse("@reboot").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for expression in [
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "* * * foo *",
+            "* * * * 1#6",
+            "0 0 30 2 *",
+        ] {
+            assert!(Cron::parse(expression).is_err(), "{expression}");
+        }
+    }
+
+    #[test]
+    fn computes_steps_ranges_and_lists() {
+        assert_eq!(
+            next_n("*/20 9-10 * * *", "UTC", "2024-01-01T00:00:00Z", 7),
+            vec![
+                "2024-01-01T09:00:00Z",
+                "2024-01-01T09:20:00Z",
+                "2024-01-01T09:40:00Z",
+                "2024-01-01T10:00:00Z",
+                "2024-01-01T10:20:00Z",
+                "2024-01-01T10:40:00Z",
+                "2024-01-02T09:00:00Z",
+            ]
+        );
+        assert_eq!(
+            next_n("5/20 0 1 jan,jul *", "UTC", "2024-01-01T00:10:00Z", 3),
+            vec![
+                "2024-01-01T00:25:00Z",
+                "2024-01-01T00:45:00Z",
+                "2024-07-01T00:05:00Z",
+            ]
+        );
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // The 13th of the month or any Friday
+        assert_eq!(
+            next_n("0 0 13 * fri", "UTC", "2024-09-01T00:00:00Z", 3),
+            vec![
+                "2024-09-06T00:00:00Z",
+                "2024-09-13T00:00:00Z",
+                "2024-09-20T00:00:00Z",
+            ]
+        );
+        // A day of month starting with `*` leaves only the day of week restricted
+        assert_eq!(
+            next_n("0 0 */2 * fri", "UTC", "2024-09-01T00:00:00Z", 2),
+            vec!["2024-09-13T00:00:00Z", "2024-09-27T00:00:00Z"]
+        );
+    }
+
+    #[test]
+    fn supports_last_and_nth_days() {
+        assert_eq!(
+            next_n("0 0 L * *", "UTC", "2024-01-31T12:00:00Z", 2),
+            vec!["2024-02-29T00:00:00Z", "2024-03-31T00:00:00Z"]
+        );
+        assert_eq!(
+            next_n("0 0 * * 5L", "UTC", "2024-01-01T00:00:00Z", 2),
+            vec!["2024-01-26T00:00:00Z", "2024-02-23T00:00:00Z"]
+        );
+        assert_eq!(
+            next_n("0 0 * * 1#2", "UTC", "2024-01-01T00:00:00Z", 2),
+            vec!["2024-01-08T00:00:00Z", "2024-02-12T00:00:00Z"]
+        );
+        assert_eq!(
+            next_n("0 0 29 2 *", "UTC", "2024-03-01T00:00:00Z", 1),
+            vec!["2028-02-29T00:00:00Z"]
+        );
+    }
+
+    #[test]
+    fn follows_timezone_and_dst() {
+        // Clocks in Riga skip from 03:00 to 04:00 on the last Sunday of March
+        assert_eq!(
+            next_n("30 3 * * *", "Europe/Riga", "2024-03-30T12:00:00Z", 2),
+            vec!["2024-03-31T01:30:00Z", "2024-04-01T00:30:00Z"]
+        );
+        // and from 04:00 back to 03:00 on the last Sunday of October
+        assert_eq!(
+            next_n("30 3 * * *", "Europe/Riga", "2024-10-26T12:00:00Z", 2),
+            vec!["2024-10-27T00:30:00Z", "2024-10-28T01:30:00Z"]
+        );
+    }
+
+    #[test]
+    fn latest_acceptable_ping_adds_grace() {
+        let schedule = Schedule::new("@hourly", "UTC").unwrap();
+        assert_eq!(
+            schedule.latest_acceptable_ping(ts("2024-01-01T10:15:00Z"), Duration::from_secs(600)),
+            Some(ts("2024-01-01T11:10:00Z"))
+        );
+    }
+
+    #[test]
+    fn validates_schedule_and_timezone() {
+        assert!(validate(Some("0 0 * * *"), None).is_ok());
+        assert!(validate(None, Some("America/New_York")).is_ok());
+        assert!(matches!(
+            validate(Some("0 0 * *"), None),
+            Err(HealthchecksValidationError::InvalidSchedule { .. })
+        ));
+        assert!(matches!(
+            validate(Some("0 0 * * *"), Some("Europe/Atlantis")),
+            Err(HealthchecksValidationError::InvalidTimezone(tz)) if tz == "Europe/Atlantis"
+        ));
+    }
+}
@@ -0,0 +1,338 @@
+use crate::ping::{deliver, maybe_add_run_id, PingKind};
+use crate::DEFAULT_USER_AGENT;
+use serde_derive::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use ureq::{Agent, AgentBuilder, Error};
+
+/// Default number of pings kept in the spool for a single check.
+const DEFAULT_MAX_ENTRIES: usize = 100;
+
+/// A ping that could not be delivered to healthchecks.io and was persisted to
+/// disk to be delivered later.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SpooledPing {
+    /// Ping URL of the check this ping belongs to, without the suffix for the kind of ping.
+    pub check_url: String,
+
+    /// Kind of ping that was sent.
+    pub kind: PingKind,
+
+    /// Time at which the ping was originally attempted, in seconds since the UNIX epoch.
+    pub timestamp: u64,
+
+    /// Run ID that was sent with the ping, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+
+    /// Request body that was sent with the ping, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+impl SpooledPing {
+    /// Full URL this ping should be delivered to.
+    fn url(&self) -> String {
+        maybe_add_run_id(
+            format!("{}{}", self.check_url, self.kind.path()),
+            self.run_id.as_deref(),
+        )
+    }
+}
+
+/// Outcome of flushing a [`Spool`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FlushResult {
+    /// Number of pings that were delivered.
+    pub delivered: usize,
+    /// Number of pings that were discarded because the server rejected them or
+    /// they could not be read back from disk.
+    pub dropped: usize,
+    /// Number of pings still waiting in the spool.
+    pub pending: usize,
+}
+
+impl std::ops::AddAssign for FlushResult {
+    fn add_assign(&mut self, other: FlushResult) {
+        self.delivered += other.delivered;
+        self.dropped += other.dropped;
+        self.pending += other.pending;
+    }
+}
+
+/// On-disk queue for pings that could not be delivered. Pings are stored in a
+/// separate directory for every check, one file per ping, and are delivered in
+/// the order they were originally sent.
+#[derive(Clone, Debug)]
+pub struct Spool {
+    pub(crate) dir: PathBuf,
+    pub(crate) max_entries: usize,
+}
+
+impl Spool {
+    /// Create a spool that stores pings inside `dir`. The directory is created
+    /// when the first ping is spooled.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Spool {
+        Spool {
+            dir: dir.into(),
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+
+    /// Set the maximum number of pings kept for a single check. When the limit is
+    /// exceeded the oldest pings are discarded first.
+    #[must_use]
+    pub fn set_max_entries(mut self, max_entries: usize) -> Spool {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// Add a ping to the end of the queue for `check`.
+    ///
+    /// # Errors
+    /// - Returns an [`io::Error`] if the ping could not be written to disk.
+    pub fn enqueue(&self, check: &str, ping: &SpooledPing) -> io::Result<()> {
+        let dir = self.check_dir(check);
+        fs::create_dir_all(&dir)?;
+        let data = serde_json::to_vec(ping)?;
+        let mut seq = entries(&dir)?.last().map_or(0, |(seq, _)| seq + 1);
+        loop {
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(dir.join(format!("{seq:020}.json")))
+            {
+                Ok(mut file) => {
+                    file.write_all(&data)?;
+                    break;
+                }
+                // Another process spooled a ping for this check at the same time
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => seq += 1,
+                Err(e) => return Err(e),
+            }
+        }
+        let entries = entries(&dir)?;
+        if entries.len() > self.max_entries {
+            for (_, path) in &entries[..entries.len() - self.max_entries] {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the pings waiting to be delivered for `check`, oldest first. Entries
+    /// that cannot be parsed are skipped.
+    ///
+    /// # Errors
+    /// - Returns an [`io::Error`] if the spool directory could not be read.
+    pub fn pending(&self, check: &str) -> io::Result<Vec<SpooledPing>> {
+        Ok(entries(&self.check_dir(check))?
+            .into_iter()
+            .filter_map(|(_, path)| read_entry(&path))
+            .collect())
+    }
+
+    /// Get the identifiers of all checks that have pings waiting in the spool.
+    ///
+    /// # Errors
+    /// - Returns an [`io::Error`] if the spool directory could not be read.
+    pub fn checks(&self) -> io::Result<Vec<String>> {
+        let mut checks = Vec::new();
+        match fs::read_dir(&self.dir) {
+            Ok(dir) => {
+                for entry in dir {
+                    let entry = entry?;
+                    if entry.file_type()?.is_dir() {
+                        checks.push(entry.file_name().to_string_lossy().into_owned());
+                    }
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        checks.sort();
+        Ok(checks)
+    }
+
+    /// Attempt to deliver the pings for every check in the spool, using
+    /// `user_agent` if provided or the library default otherwise. Delivery for a
+    /// check stops at the first ping that cannot be delivered, so that ordering is
+    /// preserved on the next attempt.
+    ///
+    /// # Errors
+    /// - Returns an [`io::Error`] if the spool could not be read or updated.
+    pub fn flush(&self, user_agent: Option<&str>) -> io::Result<FlushResult> {
+        let agent = AgentBuilder::new().timeout(Duration::from_secs(5)).build();
+        let user_agent = user_agent.unwrap_or(DEFAULT_USER_AGENT);
+        let mut result = FlushResult::default();
+        for check in self.checks()? {
+            result += self.flush_check(&check, &agent, user_agent)?;
+        }
+        Ok(result)
+    }
+
+    /// Attempt to deliver the pings spooled for a single check, in order.
+    pub(crate) fn flush_check(
+        &self,
+        check: &str,
+        agent: &Agent,
+        user_agent: &str,
+    ) -> io::Result<FlushResult> {
+        let dir = self.check_dir(check);
+        let entries = entries(&dir)?;
+        let mut result = FlushResult::default();
+        for (idx, (_, path)) in entries.iter().enumerate() {
+            let Some(ping) = read_entry(path) else {
+                fs::remove_file(path)?;
+                result.dropped += 1;
+                continue;
+            };
+            match deliver(agent, user_agent, &ping.url(), ping.body.as_deref()) {
+                Ok(()) => result.delivered += 1,
+                // The server will never accept this ping, retrying is pointless
+                Err(e) if is_rejected(&e) => result.dropped += 1,
+                Err(_) => {
+                    result.pending = entries.len() - idx;
+                    return Ok(result);
+                }
+            }
+            fs::remove_file(path)?;
+        }
+        // Clean up after ourselves, ignoring failures in case another process
+        // spooled a new ping in the meantime.
+        let _ = fs::remove_dir(&dir);
+        Ok(result)
+    }
+
+    fn check_dir(&self, check: &str) -> PathBuf {
+        let name: String = check
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(name)
+    }
+}
+
+/// List the spooled entries in `dir` along with their sequence numbers, ordered
+/// from oldest to newest.
+fn entries(dir: &Path) -> io::Result<Vec<(u64, PathBuf)>> {
+    let mut entries = Vec::new();
+    let read_dir = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(entries),
+        Err(e) => return Err(e),
+    };
+    for entry in read_dir {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            if let Some(seq) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<u64>().ok())
+            {
+                entries.push((seq, path));
+            }
+        }
+    }
+    entries.sort_unstable_by_key(|(seq, _)| *seq);
+    Ok(entries)
+}
+
+/// Whether the server permanently rejected a ping, as opposed to a failure that
+/// may succeed when retried later.
+fn is_rejected(error: &Error) -> bool {
+    matches!(error, Error::Status(code, _) if (400..500).contains(code) && *code != 429)
+}
+
+fn read_entry(path: &Path) -> Option<SpooledPing> {
+    let data = fs::read(path).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_spool() -> Spool {
+        Spool::new(std::env::temp_dir().join(format!("hc-spool-{}", uuid::Uuid::new_v4())))
+    }
+
+    fn ping(timestamp: u64) -> SpooledPing {
+        SpooledPing {
+            check_url: "http://127.0.0.1:1/2d0a34bd-854d-490e-be2c-1493f7053460".to_owned(),
+            kind: PingKind::Success,
+            timestamp,
+            run_id: None,
+            body: None,
+        }
+    }
+
+    #[test]
+    fn spool_preserves_order() {
+        let spool = temp_spool();
+        for timestamp in 0..12 {
+            spool.enqueue("check", &ping(timestamp)).unwrap();
+        }
+        let pending = spool.pending("check").unwrap();
+        let timestamps: Vec<u64> = pending.iter().map(|p| p.timestamp).collect();
+        assert_eq!(timestamps, (0..12).collect::<Vec<_>>());
+        assert_eq!(spool.checks().unwrap(), vec!["check".to_owned()]);
+        fs::remove_dir_all(&spool.dir).unwrap();
+    }
+
+    #[test]
+    fn spool_drops_oldest_entries_over_limit() {
+        let spool = temp_spool().set_max_entries(3);
+        for timestamp in 0..5 {
+            spool.enqueue("check", &ping(timestamp)).unwrap();
+        }
+        let pending = spool.pending("check").unwrap();
+        let timestamps: Vec<u64> = pending.iter().map(|p| p.timestamp).collect();
+        assert_eq!(timestamps, vec![2, 3, 4]);
+        fs::remove_dir_all(&spool.dir).unwrap();
+    }
+
+    #[test]
+    fn failed_flush_keeps_pings() {
+        let spool = temp_spool();
+        spool.enqueue("check", &ping(1)).unwrap();
+        spool.enqueue("check", &ping(2)).unwrap();
+        let result = spool.flush(None).unwrap();
+        assert_eq!(
+            result,
+            FlushResult {
+                delivered: 0,
+                dropped: 0,
+                pending: 2
+            }
+        );
+        assert_eq!(spool.pending("check").unwrap().len(), 2);
+        fs::remove_dir_all(&spool.dir).unwrap();
+    }
+
+    #[test]
+    fn spooled_ping_url_includes_kind_and_run_id() {
+        let mut ping = ping(0);
+        ping.kind = PingKind::Fail;
+        ping.run_id = Some("rid".to_owned());
+        assert_eq!(
+            ping.url(),
+            "http://127.0.0.1:1/2d0a34bd-854d-490e-be2c-1493f7053460/fail?rid=rid"
+        );
+    }
+
+    #[test]
+    fn missing_spool_is_empty() {
+        let spool = temp_spool();
+        assert!(spool.checks().unwrap().is_empty());
+        assert!(spool.pending("check").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,350 @@
+//! Statistics computed from the status changes and pings of a check, such as its
+//! uptime over a window of time and how long its runs take.
+//!
+//! ```rust
+//! # use healthchecks::model::Flip;
+//! # use healthchecks::stats::Uptime;
+//! let flips = [
+//!     Flip { timestamp: "2024-01-01T06:00:00+00:00".to_owned(), up: 0 },
+//!     Flip { timestamp: "2024-01-01T12:00:00+00:00".to_owned(), up: 1 },
+//! ];
+//! let start = "2024-01-01T00:00:00Z".parse().unwrap();
+//! let end = "2024-01-02T00:00:00Z".parse().unwrap();
+//! let uptime = Uptime::from_flips(&flips, start, end).unwrap();
+//! assert_eq!(uptime.percentage(), 75.0);
+//! assert_eq!(uptime.outages.len(), 1);
+//! assert_eq!(uptime.mttr(), Some(jiff::SignedDuration::from_hours(6)));
+//! ```
+
+use crate::errors::HealthchecksStateError;
+use crate::model::{Flip, Ping};
+use jiff::{SignedDuration, Timestamp};
+use std::collections::HashMap;
+
+/// A period during which a check was down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Outage {
+    /// When the check went down, or the start of the window if it was already down.
+    pub start: Timestamp,
+    /// When the check came back up, or the end of the window if it had not.
+    pub end: Timestamp,
+    /// Whether the check came back up within the window.
+    pub recovered: bool,
+}
+
+impl Outage {
+    /// How long the outage lasted within the window.
+    #[must_use]
+    pub fn duration(&self) -> SignedDuration {
+        self.end.duration_since(self.start)
+    }
+}
+
+/// Uptime of a check over a window of time, computed from its status changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Uptime {
+    /// Start of the window.
+    pub start: Timestamp,
+    /// End of the window.
+    pub end: Timestamp,
+    /// Periods within the window during which the check was down, oldest first.
+    pub outages: Vec<Outage>,
+}
+
+impl Uptime {
+    /// Compute the uptime between `start` and `end` from the status changes of
+    /// a check, in any order. The check is assumed to be up at the start of the
+    /// window unless a flip before it says otherwise.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksStateError::InvalidTimestamp`] if the timestamp of a flip could not be parsed.
+    pub fn from_flips(
+        flips: &[Flip],
+        start: Timestamp,
+        end: Timestamp,
+    ) -> Result<Uptime, HealthchecksStateError> {
+        let mut flips = flips
+            .iter()
+            .map(|flip| Ok((timestamp(&flip.timestamp)?, flip.up == 1)))
+            .collect::<Result<Vec<_>, HealthchecksStateError>>()?;
+        flips.sort_by_key(|(at, _)| *at);
+
+        let mut up = flips
+            .iter()
+            .rev()
+            .find(|(at, _)| *at <= start)
+            .is_none_or(|(_, up)| *up);
+        let mut since = start;
+        let mut outages = Vec::new();
+        for &(at, flip_up) in flips.iter().filter(|(at, _)| start < *at && *at < end) {
+            if flip_up == up {
+                continue;
+            }
+            if flip_up {
+                outages.push(Outage {
+                    start: since,
+                    end: at,
+                    recovered: true,
+                });
+            }
+            up = flip_up;
+            since = at;
+        }
+        if !up {
+            outages.push(Outage {
+                start: since,
+                end,
+                recovered: false,
+            });
+        }
+        Ok(Uptime {
+            start,
+            end,
+            outages,
+        })
+    }
+
+    /// Total time the check was down within the window.
+    #[must_use]
+    pub fn downtime(&self) -> SignedDuration {
+        self.outages
+            .iter()
+            .fold(SignedDuration::ZERO, |total, outage| {
+                total + outage.duration()
+            })
+    }
+
+    /// Percentage of the window during which the check was up.
+    #[must_use]
+    pub fn percentage(&self) -> f64 {
+        let window = self.end.duration_since(self.start).as_secs_f64();
+        if window <= 0.0 {
+            return 100.0;
+        }
+        100.0 * (1.0 - self.downtime().as_secs_f64() / window)
+    }
+
+    /// The longest outage within the window.
+    #[must_use]
+    pub fn longest_outage(&self) -> Option<SignedDuration> {
+        self.outages.iter().map(Outage::duration).max()
+    }
+
+    /// Mean time to recovery, the average length of the outages the check
+    /// recovered from within the window.
+    #[must_use]
+    pub fn mttr(&self) -> Option<SignedDuration> {
+        let recovered: Vec<SignedDuration> = self
+            .outages
+            .iter()
+            .filter(|outage| outage.recovered)
+            .map(Outage::duration)
+            .collect();
+        let count = i32::try_from(recovered.len()).ok().filter(|n| *n > 0)?;
+        let total = recovered
+            .into_iter()
+            .fold(SignedDuration::ZERO, |total, duration| total + duration);
+        Some(total / count)
+    }
+}
+
+/// Durations of the successful runs of a check, from its start pings paired
+/// with the success pings that finished them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunDurations {
+    /// Durations of the runs, shortest first.
+    durations: Vec<SignedDuration>,
+}
+
+impl RunDurations {
+    /// Pair the start and success pings of a check, in any order. Pings with a
+    /// run ID are paired with the start ping that has the same one, and pings
+    /// without are paired with the latest start ping without one. The duration
+    /// reported by the API is used when there is one, as ping dates are only
+    /// precise to the second, and success pings without either a duration or a
+    /// start ping in `pings` are left out.
+    ///
+    /// # Errors
+    /// - Returns [`HealthchecksStateError::InvalidTimestamp`] if the date of a ping could not be parsed.
+    pub fn from_pings(pings: &[Ping]) -> Result<RunDurations, HealthchecksStateError> {
+        let mut pings: Vec<&Ping> = pings.iter().collect();
+        pings.sort_by_key(|ping| ping.n);
+
+        let mut starts: HashMap<Option<&str>, Timestamp> = HashMap::new();
+        let mut durations = Vec::new();
+        for ping in pings {
+            match ping.type_field.as_str() {
+                "start" => {
+                    starts.insert(run_id(ping), timestamp(&ping.date)?);
+                }
+                "success" => {
+                    let start = starts.remove(&run_id(ping));
+                    let reported = ping
+                        .duration
+                        .and_then(|seconds| SignedDuration::try_from_secs_f64(seconds).ok());
+                    let duration = match (reported, start) {
+                        (Some(duration), _) => Some(duration),
+                        (None, Some(start)) => Some(timestamp(&ping.date)?.duration_since(start)),
+                        (None, None) => None,
+                    };
+                    durations.extend(duration);
+                }
+                "fail" => {
+                    starts.remove(&run_id(ping));
+                }
+                _ => {}
+            }
+        }
+        durations.sort();
+        Ok(RunDurations { durations })
+    }
+
+    /// Number of runs.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.durations.len()
+    }
+
+    /// Whether there are no runs.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.durations.is_empty()
+    }
+
+    /// The duration that `percentile` percent of the runs took at most, using
+    /// the nearest-rank method.
+    #[must_use]
+    pub fn percentile(&self, percentile: f64) -> Option<SignedDuration> {
+        if self.durations.is_empty() {
+            return None;
+        }
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * self.durations.len() as f64).ceil();
+        // The rank is between 0 and the number of runs, so it fits in a usize
+        let index = (rank as usize).saturating_sub(1);
+        self.durations.get(index).copied()
+    }
+
+    /// Duration of the longest run.
+    #[must_use]
+    pub fn max(&self) -> Option<SignedDuration> {
+        self.durations.last().copied()
+    }
+}
+
+#[cfg(feature = "v3")]
+fn run_id(ping: &Ping) -> Option<&str> {
+    ping.rid.as_deref()
+}
+
+#[cfg(not(feature = "v3"))]
+fn run_id(_ping: &Ping) -> Option<&str> {
+    None
+}
+
+fn timestamp(value: &str) -> Result<Timestamp, HealthchecksStateError> {
+    value
+        .parse()
+        .map_err(|_| HealthchecksStateError::InvalidTimestamp(value.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flip(timestamp: &str, up: i64) -> Flip {
+        Flip {
+            timestamp: timestamp.to_owned(),
+            up,
+        }
+    }
+
+    fn ping(n: i64, kind: &str, date: &str, rid: Option<&str>) -> Ping {
+        serde_json::from_value(serde_json::json!({
+            "type": kind,
+            "date": date,
+            "n": n,
+            "scheme": "http",
+            "remote_addr": "127.0.0.1",
+            "method": "GET",
+            "ua": "",
+            "rid": rid,
+        }))
+        .unwrap()
+    }
+
+    fn ts(value: &str) -> Timestamp {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn clips_outages_to_the_window() {
+        let flips = [
+            flip("2024-01-01T22:00:00+00:00", 0),
+            flip("2024-01-02T02:00:00+00:00", 1),
+            flip("2024-01-02T10:00:00+00:00", 0),
+            flip("2024-01-02T12:00:00+00:00", 1),
+            flip("2024-01-02T20:00:00+00:00", 0),
+        ];
+        let uptime = Uptime::from_flips(
+            &flips,
+            ts("2024-01-02T00:00:00Z"),
+            ts("2024-01-03T00:00:00Z"),
+        )
+        .unwrap();
+        assert_eq!(uptime.outages.len(), 3);
+        assert_eq!(uptime.outages[0].start, ts("2024-01-02T00:00:00Z"));
+        assert!(!uptime.outages[2].recovered);
+        assert_eq!(uptime.downtime(), SignedDuration::from_hours(8));
+        assert_eq!(uptime.longest_outage(), Some(SignedDuration::from_hours(4)));
+        assert_eq!(uptime.mttr(), Some(SignedDuration::from_hours(2)));
+        assert!((uptime.percentage() - 100.0 * 16.0 / 24.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn is_fully_up_without_flips() {
+        let uptime =
+            Uptime::from_flips(&[], ts("2024-01-02T00:00:00Z"), ts("2024-01-03T00:00:00Z"))
+                .unwrap();
+        assert_eq!(uptime.percentage(), 100.0);
+        assert_eq!(uptime.mttr(), None);
+        assert!(Uptime::from_flips(&[flip("yesterday", 0)], uptime.start, uptime.end).is_err());
+    }
+
+    #[test]
+    fn pairs_runs_without_run_ids() {
+        let mut finished = ping(3, "success", "2024-01-01T00:05:00+00:00", None);
+        finished.duration = Some(12.0);
+        let pings = [
+            ping(2, "success", "2024-01-01T00:01:00+00:00", None),
+            ping(1, "start", "2024-01-01T00:00:00+00:00", None),
+            finished,
+            ping(4, "success", "2024-01-01T00:06:00+00:00", None),
+        ];
+        let runs = RunDurations::from_pings(&pings).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs.percentile(50.0), Some(SignedDuration::from_secs(12)));
+        assert_eq!(runs.max(), Some(SignedDuration::from_secs(60)));
+    }
+
+    #[test]
+    #[cfg(feature = "v3")]
+    fn pairs_runs_by_run_id() {
+        let pings = [
+            ping(1, "start", "2024-01-01T00:00:00+00:00", Some("a")),
+            ping(2, "start", "2024-01-01T00:00:10+00:00", Some("b")),
+            ping(3, "success", "2024-01-01T00:00:40+00:00", Some("b")),
+            ping(4, "success", "2024-01-01T00:01:00+00:00", Some("a")),
+            ping(5, "start", "2024-01-01T00:02:00+00:00", Some("c")),
+            ping(6, "fail", "2024-01-01T00:02:05+00:00", Some("c")),
+            ping(7, "start", "2024-01-01T00:03:00+00:00", None),
+            ping(8, "success", "2024-01-01T00:03:05+00:00", None),
+        ];
+        let runs = RunDurations::from_pings(&pings).unwrap();
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs.percentile(50.0), Some(SignedDuration::from_secs(30)));
+        assert_eq!(runs.percentile(95.0), Some(SignedDuration::from_secs(60)));
+        assert_eq!(runs.percentile(0.0), Some(SignedDuration::from_secs(5)));
+        assert_eq!(runs.max(), Some(SignedDuration::from_secs(60)));
+        assert_eq!(RunDurations::default().percentile(50.0), None);
+    }
+}
@@ -0,0 +1,73 @@
+use healthchecks::background::BackgroundPinger;
+use healthchecks::ping::{get_client_with_url, PingClient};
+use healthchecks_mock::{Fault, MockCheck, MockServer};
+use std::sync::Arc;
+use std::time::Duration;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+fn client(mock: &MockServer, uuid: &str) -> Arc<PingClient> {
+    Arc::new(get_client_with_url(uuid, &mock.ping_url()).unwrap())
+}
+
+fn kinds(mock: &MockServer, uuid: &str) -> Vec<String> {
+    mock.pings_for(uuid).into_iter().map(|p| p.kind).collect()
+}
+
+#[test]
+fn coalesces_repeated_successes() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let client = client(&mock, &uuid);
+    let pinger = BackgroundPinger::spawn(8, WINDOW).unwrap();
+    assert!(pinger.report_success(&client));
+    assert!(pinger.report_success(&client));
+    assert!(pinger.flush(Duration::from_secs(10)));
+    assert_eq!(kinds(&mock, &uuid), ["success"]);
+}
+
+#[test]
+fn delivers_recovery_after_failure() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let client = client(&mock, &uuid);
+    let pinger = BackgroundPinger::spawn(8, WINDOW).unwrap();
+    assert!(pinger.report_success(&client));
+    assert!(pinger.report_failure(&client));
+    assert!(pinger.report_success(&client));
+    assert!(pinger.flush(Duration::from_secs(10)));
+    assert_eq!(kinds(&mock, &uuid), ["success", "fail", "success"]);
+    assert_eq!(mock.check(&uuid).unwrap().status, "up");
+}
+
+#[test]
+fn delivers_success_after_start() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let client = client(&mock, &uuid);
+    let pinger = BackgroundPinger::spawn(8, WINDOW).unwrap();
+    assert!(pinger.report_success(&client));
+    assert!(pinger.start_timer(&client));
+    assert!(pinger.report_success(&client));
+    assert!(pinger.flush(Duration::from_secs(10)));
+    assert_eq!(kinds(&mock, &uuid), ["success", "start", "success"]);
+    assert!(mock.pings_for(&uuid)[2].duration.is_some());
+}
+
+#[test]
+fn delivers_success_after_an_undelivered_one() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let client = Arc::new(
+        get_client_with_url(&uuid, &mock.ping_url())
+            .unwrap()
+            .set_retries(0),
+    );
+    let pinger = BackgroundPinger::spawn(8, WINDOW).unwrap();
+    mock.inject(Fault::Status(500));
+    assert!(pinger.report_success(&client));
+    assert!(pinger.flush(Duration::from_secs(10)));
+    assert!(pinger.report_success(&client));
+    assert!(pinger.flush(Duration::from_secs(10)));
+    assert_eq!(kinds(&mock, &uuid), ["success"]);
+}
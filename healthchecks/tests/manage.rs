@@ -0,0 +1,351 @@
+use healthchecks::errors::HealthchecksApiError;
+use healthchecks::manage::{get_client_with_url, ManageClient, UpsertResult};
+use healthchecks::model::{Channels, NewCheck, UniqueField, UpdatedCheck};
+use healthchecks_mock::{Fault, MockCheck, MockServer, API_KEY, READ_ONLY_API_KEY};
+use std::time::Duration;
+
+const API_VERSION: u8 = if cfg!(feature = "v3") {
+    3
+} else if cfg!(feature = "v2") {
+    2
+} else {
+    1
+};
+
+fn client(mock: &MockServer, api_key: &str) -> ManageClient {
+    get_client_with_url(api_key.to_owned(), None, mock.api_url(API_VERSION)).unwrap()
+}
+
+fn pinged_check(mock: &MockServer) -> String {
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let ping = healthchecks::ping::get_client_with_url(&uuid, &mock.ping_url()).unwrap();
+    assert!(ping.start_timer());
+    assert!(ping.report_failure());
+    assert!(ping.report_success());
+    uuid
+}
+
+#[test]
+fn get_checks() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    mock.add_check(MockCheck::new("vacuum"));
+    let checks = client(&mock, API_KEY).get_checks().unwrap();
+    assert_eq!(checks.len(), 2);
+    assert_eq!(checks[0].name, "backup");
+    assert_eq!(checks[0].id(), Some(uuid));
+    assert_eq!(checks[0].status, "new");
+}
+
+#[test]
+fn get_checks_with_invalid_key() {
+    let mock = MockServer::start();
+    let result = client(&mock, "wrong").get_checks();
+    assert!(matches!(result, Err(HealthchecksApiError::InvalidApiKey)));
+}
+
+#[test]
+fn get_checks_with_server_error() {
+    let mock = MockServer::start();
+    mock.inject(Fault::Status(500));
+    let result = client(&mock, API_KEY).get_checks();
+    assert!(matches!(
+        result,
+        Err(HealthchecksApiError::UnexpectedError(_))
+    ));
+}
+
+#[test]
+fn get_checks_with_dropped_connection() {
+    let mock = MockServer::start();
+    mock.inject(Fault::DropConnection);
+    let result = client(&mock, API_KEY).get_checks();
+    assert!(matches!(
+        result,
+        Err(HealthchecksApiError::TransportError(_))
+    ));
+}
+
+#[test]
+fn get_check() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let check = client(&mock, API_KEY).get_check(&uuid).unwrap();
+    assert_eq!(check.name, "backup");
+    assert_eq!(check.timeout, Some(86400));
+    assert!(check.unique_key.is_none());
+}
+
+#[test]
+fn get_check_with_read_only_key() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let client = client(&mock, READ_ONLY_API_KEY);
+    let checks = client.get_checks().unwrap();
+    assert!(checks[0].ping_url.is_none());
+    let unique_key = checks[0].unique_key.clone().unwrap();
+    let check = client.get_check(&unique_key).unwrap();
+    assert_eq!(check.name, mock.check(&uuid).unwrap().name);
+}
+
+#[test]
+fn get_missing_check() {
+    let mock = MockServer::start();
+    let result = client(&mock, API_KEY).get_check("2d0a34bd-854d-490e-be2c-1493f7053460");
+    assert!(matches!(result, Err(HealthchecksApiError::NoCheckFound(_))));
+}
+
+#[test]
+fn get_channels() {
+    let mock = MockServer::start();
+    let id = mock.add_channel("Ops email", "email");
+    let channels = client(&mock, API_KEY).get_channels().unwrap();
+    assert_eq!(channels.len(), 1);
+    assert_eq!(channels[0].id, id);
+    assert_eq!(channels[0].name, "Ops email");
+    assert_eq!(channels[0].kind, "email");
+}
+
+#[test]
+fn get_channels_with_read_only_key() {
+    let mock = MockServer::start();
+    let result = client(&mock, READ_ONLY_API_KEY).get_channels();
+    assert!(matches!(
+        result,
+        Err(HealthchecksApiError::PossibleReadOnlyKey)
+    ));
+}
+
+#[test]
+fn pause() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let check = client(&mock, API_KEY).pause(&uuid).unwrap();
+    assert_eq!(check.status, "paused");
+    assert_eq!(mock.check(&uuid).unwrap().status, "paused");
+}
+
+#[test]
+fn pause_with_read_only_key() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let result = client(&mock, READ_ONLY_API_KEY).pause(&uuid);
+    assert!(matches!(
+        result,
+        Err(HealthchecksApiError::PossibleReadOnlyKey)
+    ));
+}
+
+#[test]
+fn list_logged_pings() {
+    let mock = MockServer::start();
+    let uuid = pinged_check(&mock);
+    let pings = client(&mock, API_KEY).list_logged_pings(&uuid).unwrap();
+    let types: Vec<&str> = pings.iter().map(|p| p.type_field.as_str()).collect();
+    assert_eq!(types, vec!["success", "fail", "start"]);
+    assert_eq!(pings[0].n, 3);
+    assert!(pings[1].duration.is_some());
+}
+
+#[test]
+fn list_logged_pings_for_missing_check() {
+    let mock = MockServer::start();
+    let result = client(&mock, API_KEY).list_logged_pings("2d0a34bd-854d-490e-be2c-1493f7053460");
+    assert!(matches!(result, Err(HealthchecksApiError::NoCheckFound(_))));
+}
+
+#[test]
+#[cfg(feature = "v3")]
+fn get_ping_body() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let ping = healthchecks::ping::get_client_with_url(&uuid, &mock.ping_url()).unwrap();
+    assert!(ping.start_timer());
+    assert!(ping.report_failure_with_logs("disk full"));
+    let client = client(&mock, API_KEY);
+    let pings = client.list_logged_pings(&uuid).unwrap();
+    assert!(pings[1].body_url.is_none());
+    assert!(pings[0].body_url.is_some());
+    assert_eq!(
+        client.get_ping_body(&uuid, pings[0].n).unwrap(),
+        "disk full"
+    );
+    assert!(matches!(
+        client.get_ping_body(&uuid, pings[1].n),
+        Err(HealthchecksApiError::NoCheckFound(_))
+    ));
+}
+
+#[test]
+fn list_status_changes() {
+    let mock = MockServer::start();
+    let uuid = pinged_check(&mock);
+    let flips = client(&mock, API_KEY).list_status_changes(&uuid).unwrap();
+    let ups: Vec<i64> = flips.iter().map(|f| f.up).collect();
+    assert_eq!(ups, vec![1, 0]);
+}
+
+#[test]
+fn delete() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let client = client(&mock, API_KEY);
+    let check = client.delete(&uuid).unwrap();
+    assert_eq!(check.name, "backup");
+    assert!(mock.checks().is_empty());
+    assert!(matches!(
+        client.delete(&uuid),
+        Err(HealthchecksApiError::NoCheckFound(_))
+    ));
+}
+
+#[test]
+fn create_check() {
+    let mock = MockServer::start();
+    let check = NewCheck::builder()
+        .name("backup")
+        .tags(["prod", "db"])
+        .timeout(Duration::from_secs(3600))
+        .grace(Duration::from_secs(120))
+        .build()
+        .unwrap();
+    let check = client(&mock, API_KEY).create_check(check).unwrap();
+    assert_eq!(check.name, "backup");
+    assert_eq!(check.tags, "prod db");
+    assert_eq!(check.timeout, Some(3600));
+    assert_eq!(check.grace, 120);
+    assert_eq!(mock.checks().len(), 1);
+}
+
+#[test]
+fn create_existing_check() {
+    let mock = MockServer::start();
+    mock.add_check(MockCheck::new("backup"));
+    let check = NewCheck::builder()
+        .name("backup")
+        .unique([UniqueField::Name])
+        .build()
+        .unwrap();
+    let result = client(&mock, API_KEY).create_check(check);
+    assert!(matches!(
+        result,
+        Err(HealthchecksApiError::ExistingCheckMatched)
+    ));
+}
+
+#[test]
+fn create_invalid_check() {
+    let mock = MockServer::start();
+    let mut check = NewCheck::default();
+    check.channels = Some("no-such-channel".to_owned());
+    let result = client(&mock, API_KEY).create_check(check);
+    assert!(matches!(result, Err(HealthchecksApiError::NotWellFormed)));
+}
+
+#[test]
+fn create_check_out_of_bounds() {
+    let mock = MockServer::start();
+    let mut check = NewCheck::default();
+    check.timeout = Some(1);
+    let result = client(&mock, API_KEY).create_check(check);
+    assert!(matches!(result, Err(HealthchecksApiError::InvalidCheck(_))));
+    assert_eq!(mock.request_count(), 0);
+}
+
+#[test]
+fn create_check_from_builder() {
+    let mock = MockServer::start();
+    let channel = mock.add_channel("Email", "email");
+    let check = NewCheck::builder()
+        .name("backup")
+        .tags(["prod", "db"])
+        .schedule("0 3 * * *", Some("Europe/Riga"))
+        .grace(Duration::from_secs(600))
+        .manual_resume(true)
+        .channels(Channels::All)
+        .build()
+        .unwrap();
+    let check = client(&mock, API_KEY).create_check(check).unwrap();
+    assert_eq!(check.tags, "prod db");
+    assert_eq!(check.schedule.as_deref(), Some("0 3 * * *"));
+    assert!(check.manual_resume);
+    assert_eq!(check.channels, Some(channel));
+}
+
+#[test]
+fn create_check_over_limit() {
+    let mock = MockServer::start();
+    mock.set_check_limit(Some(0));
+    let result = client(&mock, API_KEY).create_check(NewCheck::default());
+    assert!(matches!(
+        result,
+        Err(HealthchecksApiError::CheckLimitReached)
+    ));
+}
+
+#[test]
+fn create_check_with_invalid_schedule() {
+    let mock = MockServer::start();
+    let mut check = NewCheck::default();
+    check.schedule = Some("0 3 * *".to_owned());
+    let result = client(&mock, API_KEY).create_check(check);
+    assert!(matches!(result, Err(HealthchecksApiError::InvalidCheck(_))));
+    assert_eq!(mock.request_count(), 0);
+}
+
+#[test]
+fn upsert_check() {
+    let mock = MockServer::start();
+    let client = client(&mock, API_KEY);
+    let builder = NewCheck::builder()
+        .name("backup")
+        .unique([UniqueField::Name]);
+    let (result, _) = client
+        .upsert_check(builder.clone().desc("first").build().unwrap())
+        .unwrap();
+    assert!(matches!(result, UpsertResult::Created));
+    let (result, check) = client
+        .upsert_check(builder.desc("second").build().unwrap())
+        .unwrap();
+    assert!(matches!(result, UpsertResult::Updated));
+    assert_eq!(check.desc, "second");
+    assert_eq!(mock.checks().len(), 1);
+}
+
+#[test]
+fn update_check() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let check = client(&mock, API_KEY)
+        .update_check(
+            UpdatedCheck::builder()
+                .schedule("0 3 * * *", Some("Europe/Riga"))
+                .build()
+                .unwrap(),
+            &uuid,
+        )
+        .unwrap();
+    assert_eq!(check.schedule.as_deref(), Some("0 3 * * *"));
+    assert_eq!(check.tz.as_deref(), Some("Europe/Riga"));
+    assert_eq!(check.timeout, None);
+}
+
+#[test]
+fn update_missing_check() {
+    let mock = MockServer::start();
+    let result = client(&mock, API_KEY).update_check(
+        UpdatedCheck::default(),
+        "2d0a34bd-854d-490e-be2c-1493f7053460",
+    );
+    assert!(matches!(result, Err(HealthchecksApiError::NoCheckFound(_))));
+}
+
+#[test]
+fn update_check_with_unknown_channel() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let mut check = UpdatedCheck::default();
+    check.channels = Some("missing".to_owned());
+    let result = client(&mock, API_KEY).update_check(check, &uuid);
+    assert!(matches!(result, Err(HealthchecksApiError::NotWellFormed)));
+}
@@ -0,0 +1,185 @@
+use healthchecks::ping::{get_client_with_url, PingClient, PingKind};
+use healthchecks::spool::Spool;
+use healthchecks_mock::{Fault, MockCheck, MockServer, PING_KEY};
+use std::time::Duration;
+use uuid::Uuid;
+
+fn client(mock: &MockServer, uuid: &str) -> PingClient {
+    get_client_with_url(uuid, &mock.ping_url()).unwrap()
+}
+
+#[test]
+fn report_success() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    assert!(client(&mock, &uuid).report_success());
+    let pings = mock.pings_for(&uuid);
+    assert_eq!(pings.len(), 1);
+    assert_eq!(pings[0].kind, "success");
+    assert_eq!(pings[0].method, "GET");
+    assert!(pings[0].user_agent.starts_with("healthchecks-rs/"));
+    assert_eq!(mock.check(&uuid).unwrap().status, "up");
+}
+
+#[test]
+fn report_success_with_run_id() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let run_id = Uuid::new_v4();
+    let client = client(&mock, &uuid);
+    assert!(client.start_timer_with_run_id(Some(&run_id)));
+    assert!(client.report_success_with_run_id(Some(&run_id)));
+    let pings = mock.pings_for(&uuid);
+    assert_eq!(pings[1].run_id, Some(run_id.to_string()));
+    assert!(pings[1].duration.is_some());
+}
+
+#[test]
+fn report_failure() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    assert!(client(&mock, &uuid).report_failure());
+    assert_eq!(mock.pings_for(&uuid)[0].kind, "fail");
+    assert_eq!(mock.check(&uuid).unwrap().status, "down");
+}
+
+#[test]
+fn report_failure_with_run_id() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let run_id = Uuid::new_v4();
+    assert!(client(&mock, &uuid).report_failure_with_run_id(Some(&run_id)));
+    assert_eq!(mock.pings_for(&uuid)[0].run_id, Some(run_id.to_string()));
+}
+
+#[test]
+fn report_failure_with_logs() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    assert!(client(&mock, &uuid).report_failure_with_logs("disk full"));
+    let ping = &mock.pings_for(&uuid)[0];
+    assert_eq!(ping.kind, "fail");
+    assert_eq!(ping.method, "POST");
+    assert_eq!(ping.body, "disk full");
+}
+
+#[test]
+fn report_failure_with_logs_and_run_id() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let run_id = Uuid::new_v4();
+    assert!(client(&mock, &uuid).report_failure_with_logs_and_run_id("disk full", Some(&run_id)));
+    let ping = &mock.pings_for(&uuid)[0];
+    assert_eq!(ping.body, "disk full");
+    assert_eq!(ping.run_id, Some(run_id.to_string()));
+}
+
+#[test]
+fn send_any_kind_with_a_body() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let client = client(&mock, &uuid);
+    let run_id = Uuid::new_v4();
+    assert!(client.send(PingKind::Log, Some("halfway"), None));
+    assert!(client.send(PingKind::ExitCode(3), Some("disk full"), Some(&run_id)));
+    let pings = mock.pings_for(&uuid);
+    assert_eq!(pings[0].kind, "log");
+    assert_eq!(pings[0].body, "halfway");
+    assert_eq!(pings[1].exit_status, Some(3));
+    assert_eq!(pings[1].body, "disk full");
+    assert_eq!(pings[1].run_id, Some(run_id.to_string()));
+    assert_eq!(mock.check(&uuid).unwrap().status, "down");
+}
+
+#[test]
+fn start_timer() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    assert!(client(&mock, &uuid).start_timer());
+    assert_eq!(mock.pings_for(&uuid)[0].kind, "start");
+    assert!(mock.check(&uuid).unwrap().started);
+}
+
+#[test]
+fn custom_user_agent() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    assert!(client(&mock, &uuid)
+        .set_user_agent("crontab")
+        .report_success());
+    assert_eq!(mock.pings_for(&uuid)[0].user_agent, "crontab");
+}
+
+#[test]
+fn retries_after_faults() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    mock.inject(Fault::Status(500));
+    mock.inject(Fault::RateLimited);
+    mock.inject(Fault::DropConnection);
+    mock.inject(Fault::Latency(Duration::from_millis(100)));
+    assert!(client(&mock, &uuid).report_success());
+    assert_eq!(mock.request_count(), 4);
+    assert_eq!(mock.pings_for(&uuid).len(), 1);
+}
+
+#[test]
+fn gives_up_after_retries() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    for _ in 0..3 {
+        mock.inject(Fault::Status(503));
+    }
+    assert!(!client(&mock, &uuid).set_retries(2).report_success());
+    assert_eq!(mock.request_count(), 3);
+    assert!(mock.pings_for(&uuid).is_empty());
+}
+
+#[test]
+fn times_out_slow_requests() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    mock.inject(Fault::Latency(Duration::from_secs(2)));
+    let client = client(&mock, &uuid)
+        .set_timeout(Duration::from_millis(200))
+        .set_retries(0);
+    assert!(!client.report_success());
+}
+
+#[test]
+fn spooled_pings_are_delivered_in_order() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let dir = std::env::temp_dir().join(format!("hc-spool-{}", Uuid::new_v4()));
+    let client = client(&mock, &uuid)
+        .set_retries(0)
+        .set_spool(Spool::new(&dir));
+    mock.inject(Fault::DropConnection);
+    assert!(!client.start_timer());
+    mock.inject(Fault::Status(502));
+    assert!(!client.report_failure_with_logs("oops"));
+    assert!(client.report_success());
+    let kinds: Vec<String> = mock.pings_for(&uuid).into_iter().map(|p| p.kind).collect();
+    assert_eq!(kinds, vec!["start", "fail", "success"]);
+    assert!(Spool::new(&dir).checks().unwrap().is_empty());
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn from_url_with_uuid() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("backup"));
+    let client = PingClient::from_url(&format!("{}/{uuid}", mock.ping_url())).unwrap();
+    assert!(client.report_success());
+    assert_eq!(mock.pings_for(&uuid).len(), 1);
+}
+
+#[test]
+fn from_url_with_slug() {
+    let mock = MockServer::start();
+    let uuid = mock.add_check(MockCheck::new("Nightly Backup"));
+    let url = format!("{}/{PING_KEY}/nightly-backup", mock.ping_url());
+    let client = PingClient::from_url(&url).unwrap();
+    assert!(client.report_failure());
+    assert_eq!(mock.pings_for(&uuid)[0].kind, "fail");
+}
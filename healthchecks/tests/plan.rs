@@ -0,0 +1,72 @@
+use healthchecks::manage::{get_client_with_url, ManageClient};
+use healthchecks::model::Channels;
+use healthchecks::plan::{Change, DesiredCheck, Plan};
+use healthchecks_mock::{MockCheck, MockServer, API_KEY};
+
+const API_VERSION: u8 = if cfg!(feature = "v3") {
+    3
+} else if cfg!(feature = "v2") {
+    2
+} else {
+    1
+};
+
+fn client(mock: &MockServer) -> ManageClient {
+    get_client_with_url(API_KEY.to_owned(), None, mock.api_url(API_VERSION)).unwrap()
+}
+
+fn desired() -> Vec<DesiredCheck> {
+    vec![
+        DesiredCheck {
+            name: "backup".to_owned(),
+            tags: Some(vec!["prod".to_owned()]),
+            grace: Some(600),
+            channels: Some(Channels::List(vec!["Email".to_owned()])),
+            ..Default::default()
+        },
+        DesiredCheck {
+            name: "vacuum".to_owned(),
+            schedule: Some("0 3 * * *".to_owned()),
+            tz: Some("Europe/Riga".to_owned()),
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn apply_converges() {
+    let mock = MockServer::start();
+    let email = mock.add_channel("Email", "email");
+    mock.add_check(MockCheck::new("backup"));
+    let stale = mock.add_check(MockCheck::new("stale"));
+    let client = client(&mock);
+
+    let plan = Plan::fetch(&client, &desired(), true).unwrap();
+    assert!(matches!(
+        plan.changes(),
+        [Change::Update { .. }, Change::Create { .. }, Change::Delete { id, .. }] if *id == stale
+    ));
+    plan.apply(&client).unwrap();
+
+    let checks = mock.checks();
+    assert_eq!(checks.len(), 2);
+    let backup = checks.iter().find(|c| c.name == "backup").unwrap();
+    assert_eq!(backup.grace, 600);
+    assert_eq!(backup.tags, "prod");
+    assert_eq!(backup.channels, vec![email]);
+    let vacuum = checks.iter().find(|c| c.name == "vacuum").unwrap();
+    assert_eq!(vacuum.schedule.as_deref(), Some("0 3 * * *"));
+
+    assert!(Plan::fetch(&client, &desired(), true).unwrap().is_empty());
+}
+
+#[test]
+fn keeps_unlisted_checks_without_prune() {
+    let mock = MockServer::start();
+    mock.add_channel("Email", "email");
+    mock.add_check(MockCheck::new("stale"));
+    let client = client(&mock);
+    let plan = Plan::fetch(&client, &desired(), false).unwrap();
+    plan.apply(&client).unwrap();
+    assert_eq!(mock.checks().len(), 3);
+}
@@ -1,4 +1,6 @@
 use clap::Parser;
+use clap_complete::Shell;
+use std::path::PathBuf;
 
 /// This is useful to have a good-looking default in the clap generated help.
 const FAKE_EMPTY_STRING: &str = "\"\"";
@@ -6,6 +8,8 @@ const FAKE_EMPTY_STRING: &str = "\"\"";
 #[derive(Parser)]
 #[command(author, version, about)]
 pub(crate) struct Opts {
+    #[command(subcommand)]
+    pub(crate) subcommand: Option<SubCommand>,
     /// command to execute and monitor
     #[arg(short = 'X', long = "exec")]
     pub(crate) command: Vec<String>,
@@ -26,8 +30,26 @@ pub(crate) struct Opts {
         default_value = FAKE_EMPTY_STRING
     )]
     pub(crate) user_agent: String,
+    /// directory to store pings that could not be delivered, to be retried on the next run
+    #[arg(short = 's', long = "spool-dir", global = true)]
+    pub(crate) spool_dir: Option<PathBuf>,
+    /// prints the completions for the given shell and exits
+    #[arg(long = "completions", value_enum, exclusive = true)]
+    pub(crate) completions: Option<Shell>,
+    /// writes the man page to the given directory and exits
+    #[arg(long = "man", exclusive = true)]
+    pub(crate) man_dir: Option<PathBuf>,
 }
 
+#[derive(Parser)]
+pub(crate) enum SubCommand {
+    Flush(Flush),
+}
+
+/// Deliver the pings stored in the spool directory
+#[derive(Parser)]
+pub(crate) struct Flush {}
+
 impl Opts {
     pub(crate) fn has_user_agent(self: &Opts) -> bool {
         self.user_agent == FAKE_EMPTY_STRING
@@ -42,4 +64,11 @@ mod test {
     fn cli_assert() {
         <Opts as clap::CommandFactory>::command().debug_assert();
     }
+
+    #[test]
+    fn completions_exit_without_a_command() {
+        let parse = |args: &[&str]| <Opts as clap::Parser>::try_parse_from(args);
+        assert!(parse(&["monitor", "--completions", "fish"]).is_ok());
+        assert!(parse(&["monitor", "--completions", "fish", "-X", "true"]).is_err());
+    }
 }
@@ -1,35 +1,67 @@
 mod cli;
 mod exec;
 
-use crate::{cli::Opts, exec::run_with_retry};
-use clap::Parser;
+use crate::{
+    cli::{Opts, SubCommand},
+    exec::run_with_retry,
+};
+use clap::{CommandFactory, Parser};
 use color_eyre::{eyre::eyre, Result};
-use healthchecks::ping::get_client;
+use healthchecks::{
+    env::{HEALTHCHECKS_SPOOL_DIR, HEALTHCHECKS_USERAGENT},
+    ping::PingClient,
+    spool::Spool,
+};
 use std::env::var;
-
-const HEALTHCHECKS_CHECK_ID_VAR: &str = "HEALTHCHECKS_CHECK_ID";
-
-#[derive(Debug)]
-struct Settings {
-    check_id: String,
-    ua: Option<String>,
-}
+use std::path::PathBuf;
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let opts = Opts::parse();
-    let ua = if opts.has_user_agent() {
-        var("HEALTHCHECKS_USERAGENT").ok()
-    } else {
-        Some(opts.user_agent)
-    };
-    let settings = Settings {
-        check_id: var(HEALTHCHECKS_CHECK_ID_VAR)?,
-        ua,
-    };
-    let mut client = get_client(&settings.check_id)?;
-    if let Some(user_agent) = settings.ua {
-        client = client.set_user_agent(&user_agent);
+    if let Some(shell) = opts.completions {
+        clap_complete::generate(
+            shell,
+            &mut Opts::command(),
+            "healthchecks-monitor",
+            &mut std::io::stdout(),
+        );
+        return Ok(());
+    }
+    if let Some(dir) = opts.man_dir {
+        std::fs::create_dir_all(&dir)?;
+        clap_mangen::generate_to(Opts::command(), dir)?;
+        return Ok(());
+    }
+    if let Some(SubCommand::Flush(_)) = opts.subcommand {
+        let ua = if opts.has_user_agent() {
+            var(HEALTHCHECKS_USERAGENT).ok()
+        } else {
+            Some(opts.user_agent)
+        };
+        let Some(spool_dir) = opts
+            .spool_dir
+            .or_else(|| var(HEALTHCHECKS_SPOOL_DIR).ok().map(PathBuf::from))
+        else {
+            return Err(eyre!(
+                "A spool directory must be provided with --spool-dir or {HEALTHCHECKS_SPOOL_DIR}"
+            ));
+        };
+        let result = Spool::new(spool_dir).flush(ua.as_deref())?;
+        println!(
+            "Delivered {} ping(s), dropped {}, {} still pending",
+            result.delivered, result.dropped, result.pending
+        );
+        if result.pending > 0 {
+            return Err(eyre!("Failed to deliver {} ping(s)", result.pending));
+        }
+        return Ok(());
+    }
+    let mut client = PingClient::from_env()?;
+    if !opts.has_user_agent() {
+        client = client.set_user_agent(&opts.user_agent);
+    }
+    if let Some(spool_dir) = opts.spool_dir {
+        client = client.set_spool(Spool::new(spool_dir));
     }
     if opts.timer && !client.start_timer() {
         eprintln!("Failed to start timer");